@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use ggez::event;
+use ggez::event::EventHandler;
+use ggez::graphics::Canvas;
+use ggez::graphics::Color;
+use ggez::Context;
+use ggez::ContextBuilder;
+use ggez::GameError;
+
+use crate::ecs::world::World;
+
+pub type BoxedSystem = Box<dyn FnMut(&mut World, &mut Context) -> Result<(), GameError>>;
+
+/// A reusable piece of gameplay that registers its own systems and resources.
+pub trait Plugin {
+    fn build(&self, app: &mut App);
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Stage {
+    /// Runs once, after the context is created and before the first frame.
+    Startup,
+    /// Runs once per frame, before the fixed steps.
+    Update,
+    /// Runs zero or more times per frame, always advancing by [`FixedTime::step`].
+    FixedUpdate,
+    /// Runs with the frame [`Canvas`] available as a resource.
+    Draw,
+}
+
+/// Color the frame is cleared with before the draw stage runs.
+#[derive(Debug, Clone, Copy)]
+pub struct ClearColor(pub Color);
+
+impl Default for ClearColor {
+    fn default() -> Self {
+        ClearColor(Color::BLACK)
+    }
+}
+
+/// Accumulates frame time and turns it into fixed simulation steps.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedTime {
+    pub step: Duration,
+    /// Most steps run in a single frame, the rest of the time is dropped so a
+    /// long hitch does not make the simulation spiral trying to catch up.
+    pub max_steps: u32,
+    accumulator: Duration,
+}
+
+impl FixedTime {
+    pub fn new(step: Duration) -> FixedTime {
+        FixedTime {
+            step,
+            max_steps: 8,
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    pub fn from_hz(hz: f64) -> FixedTime {
+        FixedTime::new(Duration::from_secs_f64(1.0 / hz))
+    }
+
+    #[inline]
+    pub fn delta_secs(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    /// How far between the last step and the next one the frame is, from 0 to 1.
+    #[inline]
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+
+    pub fn accumulate(&mut self, delta: Duration) {
+        self.accumulator = (self.accumulator + delta).min(self.step * self.max_steps);
+    }
+
+    /// Consumes one step from the accumulator, if there is enough time left.
+    pub fn expend(&mut self) -> bool {
+        if self.accumulator < self.step {
+            return false;
+        }
+
+        self.accumulator -= self.step;
+
+        true
+    }
+}
+
+impl Default for FixedTime {
+    fn default() -> Self {
+        FixedTime::from_hz(120.0)
+    }
+}
+
+#[derive(Default)]
+pub struct Schedule {
+    startup: Vec<BoxedSystem>,
+    update: Vec<BoxedSystem>,
+    fixed_update: Vec<BoxedSystem>,
+    draw: Vec<BoxedSystem>,
+}
+
+impl Schedule {
+    pub fn add_system<S>(&mut self, stage: Stage, system: S)
+    where
+        S: FnMut(&mut World, &mut Context) -> Result<(), GameError> + 'static,
+    {
+        let systems = match stage {
+            Stage::Startup => &mut self.startup,
+            Stage::Update => &mut self.update,
+            Stage::FixedUpdate => &mut self.fixed_update,
+            Stage::Draw => &mut self.draw,
+        };
+
+        systems.push(Box::new(system));
+    }
+
+    pub fn run(
+        &mut self,
+        stage: Stage,
+        world: &mut World,
+        ctx: &mut Context,
+    ) -> Result<(), GameError> {
+        let systems = match stage {
+            Stage::Startup => &mut self.startup,
+            Stage::Update => &mut self.update,
+            Stage::FixedUpdate => &mut self.fixed_update,
+            Stage::Draw => &mut self.draw,
+        };
+
+        for system in systems.iter_mut() {
+            system(world, ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct App {
+    context: ContextBuilder,
+    world: World,
+    schedule: Schedule,
+}
+
+impl App {
+    pub fn new(context: ContextBuilder) -> App {
+        let mut world = World::new();
+
+        world.insert_resource(FixedTime::default());
+
+        App {
+            context,
+            world,
+            schedule: Schedule::default(),
+        }
+    }
+
+    #[inline]
+    pub fn world(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    pub fn add_plugin<P: Plugin>(&mut self, plugin: P) -> &mut Self {
+        plugin.build(self);
+        self
+    }
+
+    pub fn add_system<S>(&mut self, stage: Stage, system: S) -> &mut Self
+    where
+        S: FnMut(&mut World, &mut Context) -> Result<(), GameError> + 'static,
+    {
+        self.schedule.add_system(stage, system);
+        self
+    }
+
+    pub fn insert_resource<R: 'static>(&mut self, resource: R) -> &mut Self {
+        self.world.insert_resource(resource);
+        self
+    }
+
+    /// Creates the ggez context, runs the startup stage and hands control to
+    /// the event loop, which never returns.
+    pub fn run(&mut self) -> Result<(), GameError> {
+        let (mut ctx, event_loop) = self.context.clone().build()?;
+
+        let mut runner = AppRunner {
+            world: std::mem::take(&mut self.world),
+            schedule: std::mem::take(&mut self.schedule),
+        };
+
+        runner
+            .schedule
+            .run(Stage::Startup, &mut runner.world, &mut ctx)?;
+
+        event::run(ctx, event_loop, runner)
+    }
+}
+
+struct AppRunner {
+    world: World,
+    schedule: Schedule,
+}
+
+impl AppRunner {
+    fn expend_fixed_step(&self) -> bool {
+        match self.world.resource_mut::<FixedTime>() {
+            Some(mut time) => time.expend(),
+            None => false,
+        }
+    }
+}
+
+impl EventHandler for AppRunner {
+    fn update(&mut self, ctx: &mut Context) -> Result<(), GameError> {
+        self.schedule.run(Stage::Update, &mut self.world, ctx)?;
+
+        if let Some(mut time) = self.world.resource_mut::<FixedTime>() {
+            time.accumulate(ctx.time.delta());
+        }
+
+        while self.expend_fixed_step() {
+            self.schedule
+                .run(Stage::FixedUpdate, &mut self.world, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> Result<(), GameError> {
+        let clear_color = self
+            .world
+            .resource::<ClearColor>()
+            .map(|color| *color)
+            .unwrap_or_default();
+
+        self.world
+            .insert_resource(Canvas::from_frame(ctx, clear_color.0));
+
+        self.schedule.run(Stage::Draw, &mut self.world, ctx)?;
+
+        match self.world.remove_resource::<Canvas>() {
+            Some(canvas) => canvas.finish(ctx),
+            None => Err(GameError::CustomError(
+                "Canvas resource was removed during the draw stage".to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use super::component::ComponentId;
+use super::entity::Entity;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ArchetypeId(usize);
+
+impl ArchetypeId {
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Every time a new archetype is created the world generation moves forward,
+/// so anything caching archetype matches only has to look at the new ones.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+pub struct ArchetypeGeneration(usize);
+
+impl ArchetypeGeneration {
+    #[inline]
+    pub fn initial() -> ArchetypeGeneration {
+        ArchetypeGeneration(0)
+    }
+}
+
+#[derive(Debug)]
+pub struct Archetype {
+    id: ArchetypeId,
+    components: Vec<ComponentId>,
+    entities: Vec<Entity>,
+}
+
+impl Archetype {
+    #[inline]
+    pub fn id(&self) -> ArchetypeId {
+        self.id
+    }
+
+    #[inline]
+    pub fn components(&self) -> &[ComponentId] {
+        &self.components
+    }
+
+    #[inline]
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    #[inline]
+    pub fn contains(&self, component_id: &ComponentId) -> bool {
+        self.components.binary_search(component_id).is_ok()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Archetypes {
+    archetypes: Vec<Archetype>,
+    indices: HashMap<Vec<ComponentId>, ArchetypeId>,
+    locations: HashMap<Entity, ArchetypeId>,
+}
+
+impl Archetypes {
+    #[inline]
+    pub fn generation(&self) -> ArchetypeGeneration {
+        ArchetypeGeneration(self.archetypes.len())
+    }
+
+    #[inline]
+    pub fn get(&self, id: ArchetypeId) -> Option<&Archetype> {
+        self.archetypes.get(id.0)
+    }
+
+    /// Returns the archetypes created since `generation`.
+    pub fn since(&self, generation: ArchetypeGeneration) -> &[Archetype] {
+        &self.archetypes[generation.0.min(self.archetypes.len())..]
+    }
+
+    pub fn location(&self, entity: &Entity) -> Option<ArchetypeId> {
+        self.locations.get(entity).copied()
+    }
+
+    /// Empties every archetype while keeping the archetypes themselves, so
+    /// generations already seen by queries stay valid.
+    pub fn clear_entities(&mut self) {
+        for archetype in self.archetypes.iter_mut() {
+            archetype.entities.clear();
+        }
+
+        self.locations.clear();
+    }
+
+    pub fn remove(&mut self, entity: &Entity) {
+        if let Some(id) = self.locations.remove(entity) {
+            self.archetypes[id.0]
+                .entities
+                .retain(|other| other != entity);
+        }
+    }
+
+    /// Moves `entity` to the archetype made of `components`, creating it if needed.
+    pub fn insert(&mut self, entity: Entity, mut components: Vec<ComponentId>) -> ArchetypeId {
+        components.sort();
+        components.dedup();
+
+        let id = self.get_or_insert(components);
+
+        if let Some(previous) = self.locations.insert(entity.clone(), id) {
+            if previous == id {
+                return id;
+            }
+
+            self.archetypes[previous.0]
+                .entities
+                .retain(|other| other != &entity);
+        }
+
+        self.archetypes[id.0].entities.push(entity);
+
+        id
+    }
+
+    /// Adds `entities`, which have no archetype yet, to the one made of `components`.
+    pub fn insert_batch(
+        &mut self,
+        entities: &[Entity],
+        mut components: Vec<ComponentId>,
+    ) -> ArchetypeId {
+        components.sort();
+        components.dedup();
+
+        let id = self.get_or_insert(components);
+
+        self.locations
+            .extend(entities.iter().map(|entity| (entity.clone(), id)));
+        self.archetypes[id.0]
+            .entities
+            .extend(entities.iter().cloned());
+
+        id
+    }
+
+    fn get_or_insert(&mut self, components: Vec<ComponentId>) -> ArchetypeId {
+        let Archetypes {
+            archetypes,
+            indices,
+            ..
+        } = self;
+
+        *indices.entry(components.clone()).or_insert_with(|| {
+            let id = ArchetypeId(archetypes.len());
+
+            archetypes.push(Archetype {
+                id,
+                components,
+                entities: Vec::new(),
+            });
+
+            id
+        })
+    }
+}
@@ -1,4 +1,6 @@
+use std::any::Any;
 use std::any::TypeId;
+use std::cell::RefCell;
 
 use super::{entity::Entity, world::Storages, TypeIdMap};
 
@@ -14,20 +16,45 @@ pub trait Bundle {
     );
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct ComponentId(usize);
 
+impl ComponentId {
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for ComponentId {
+    fn from(value: usize) -> Self {
+        ComponentId(value)
+    }
+}
+
+/// Clones a type erased component, used to snapshot and restore storages.
+pub type CloneFn = fn(&dyn Any) -> Box<RefCell<dyn Any>>;
+
 #[derive(Debug, Clone)]
 pub struct ComponentInfo {
     pub id: ComponentId,
+    pub clone: Option<CloneFn>,
 }
 
 impl ComponentInfo {
     pub fn new(id: ComponentId) -> ComponentInfo {
-        ComponentInfo { id }
+        ComponentInfo { id, clone: None }
     }
 }
 
+fn clone_component<T: Component + Clone>(component: &dyn Any) -> Box<RefCell<dyn Any>> {
+    let component = component
+        .downcast_ref::<T>()
+        .expect("Component type does not match its storage");
+
+    Box::new(RefCell::new(component.clone()))
+}
+
 #[derive(Debug, Default)]
 pub struct Components {
     components: Vec<ComponentInfo>,
@@ -55,6 +82,24 @@ impl Components {
         component_id
     }
 
+    pub fn register_clone<T: Component + Clone>(&mut self, storages: &mut Storages) -> ComponentId {
+        let component_id = self.init_component::<T>(storages);
+
+        self.components[component_id.0].clone = Some(clone_component::<T>);
+
+        component_id
+    }
+
+    #[inline]
+    pub fn info(&self, id: &ComponentId) -> Option<&ComponentInfo> {
+        self.components.get(id.0)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &ComponentInfo> {
+        self.components.iter()
+    }
+
     #[inline]
     pub fn init_component_inner(components: &mut Vec<ComponentInfo>) -> usize {
         let index = components.len();
@@ -66,6 +111,21 @@ impl Components {
     }
 }
 
+/// Adds the bundle only when there is one, for components picked at runtime.
+impl<B: Bundle> Bundle for Option<B> {
+    fn components_ids(
+        &self,
+        entity: Entity,
+        components: &mut Components,
+        storages: &mut Storages,
+        ids: &mut impl FnMut(ComponentId),
+    ) {
+        if let Some(bundle) = self {
+            bundle.components_ids(entity, components, storages, ids);
+        }
+    }
+}
+
 macro_rules! tuple_impls {
     ($head_ty:ident) => {
         tuple_impl!($head_ty);
@@ -2,24 +2,85 @@ use std::collections::HashMap;
 
 use super::component::ComponentId;
 
-#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
-pub struct Entity(usize);
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Entity {
+    id: usize,
+    /// Bumped every time the id is reused, so stale handles match nothing.
+    generation: u32,
+}
+
+impl Entity {
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Entities {
     entities: Vec<Entity>,
     components: HashMap<Entity, Vec<ComponentId>>,
+    /// Despawned entities, their ids are reused by the next allocations.
+    free: Vec<Entity>,
+    next: usize,
 }
 
 impl Entities {
     pub fn alloc(&mut self) -> Entity {
-        let entity: Entity = (self.entities.len()).into();
+        let entity = match self.free.pop() {
+            Some(entity) => Entity {
+                id: entity.id,
+                generation: entity.generation.wrapping_add(1),
+            },
+            None => {
+                let entity = Entity::from(self.next);
+
+                self.next += 1;
+                entity
+            }
+        };
 
         self.entities.push(entity.clone());
 
         entity
     }
 
+    /// Allocates `count` entities with consecutive ids. Unlike [`Entities::alloc`]
+    /// it never takes ids from the free list, which would break up the range,
+    /// so despawned ids are left for the next single allocations.
+    pub fn alloc_batch(&mut self, count: usize) -> Vec<Entity> {
+        let entities: Vec<Entity> = (self.next..self.next + count).map(Entity::from).collect();
+
+        self.next += count;
+        self.entities.extend(entities.iter().cloned());
+        self.components.reserve(count);
+
+        entities
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.iter()
+    }
+
+    pub fn contains(&self, entity: &Entity) -> bool {
+        self.components.contains_key(entity)
+    }
+
+    /// Forgets `entity`, returning the components it had.
+    pub fn free(&mut self, entity: &Entity) -> Option<Vec<ComponentId>> {
+        let components = self.components.remove(entity)?;
+
+        self.entities.retain(|other| other != entity);
+        self.free.push(entity.clone());
+
+        Some(components)
+    }
+
     pub fn set_components(&mut self, entity: Entity, components_ids: Vec<ComponentId>) {
         self.components
             .entry(entity)
@@ -27,6 +88,19 @@ impl Entities {
             .or_insert(components_ids);
     }
 
+    pub fn replace_components(&mut self, entity: Entity, components_ids: Vec<ComponentId>) {
+        self.components.insert(entity, components_ids);
+    }
+
+    /// Sets the components of entities just allocated, which all share them.
+    pub fn set_batch(&mut self, entities: &[Entity], components_ids: &[ComponentId]) {
+        self.components.extend(
+            entities
+                .iter()
+                .map(|entity| (entity.clone(), components_ids.to_vec())),
+        );
+    }
+
     pub fn components(&self, entity: &Entity) -> Option<Vec<ComponentId>> {
         self.components.get(entity).cloned()
     }
@@ -34,6 +108,9 @@ impl Entities {
 
 impl From<usize> for Entity {
     fn from(value: usize) -> Self {
-        Entity(value)
+        Entity {
+            id: value,
+            generation: 0,
+        }
     }
 }
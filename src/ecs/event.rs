@@ -0,0 +1,54 @@
+use std::slice::Iter;
+
+/// Resource buffering events of one type until the system producing them
+/// clears it, readers see everything sent since then.
+#[derive(Debug, Clone)]
+pub struct Events<T> {
+    events: Vec<T>,
+}
+
+impl<T> Events<T> {
+    pub fn new() -> Events<T> {
+        Events { events: Vec::new() }
+    }
+
+    #[inline]
+    pub fn send(&mut self, event: T) {
+        self.events.push(event);
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.events.iter()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.events.drain(..)
+    }
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Events::new()
+    }
+}
+
+impl<T> Extend<T> for Events<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.events.extend(iter);
+    }
+}
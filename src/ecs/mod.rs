@@ -1,7 +1,9 @@
 use std::any::TypeId;
 
+pub mod archetype;
 pub mod component;
 pub mod entity;
+pub mod event;
 pub mod world;
 
 pub type TypeIdMap<V> = rustc_hash::FxHashMap<TypeId, V>;
@@ -1,24 +1,34 @@
 use std::any::Any;
+use std::any::TypeId;
+use std::cell::Cell;
+use std::cell::Ref;
 use std::cell::RefCell;
+use std::cell::RefMut;
 use std::collections::HashMap;
 
 use self::query::Query;
 use self::query::QueryState;
+use self::snapshot::Snapshot;
 
+use super::archetype::Archetypes;
 use super::component::Bundle;
 use super::component::Component;
 use super::component::ComponentId;
 use super::component::Components;
 use super::entity::Entities;
 use super::entity::Entity;
+use super::TypeIdMap;
 
 pub mod query;
+pub mod snapshot;
 
 #[derive(Debug, Default)]
 pub struct World {
     entities: Entities,
+    archetypes: Archetypes,
     components: Components,
     storages: Storages,
+    resources: TypeIdMap<Box<dyn Any>>,
 }
 
 impl World {
@@ -31,6 +41,11 @@ impl World {
         self.components.init_component::<T>(&mut self.storages)
     }
 
+    /// Registers `T` to be captured by [`World::snapshot`].
+    pub fn register_snapshot<T: Component + Clone>(&mut self) -> ComponentId {
+        self.components.register_clone::<T>(&mut self.storages)
+    }
+
     pub fn spawn<B: Bundle>(&mut self, bundle: B) -> Entity {
         let entity = self.entities.alloc();
 
@@ -48,24 +63,207 @@ impl World {
         self.entities
             .set_components(entity.clone(), components_ids.clone());
 
+        if let Some(components_ids) = self.entities.components(&entity) {
+            self.archetypes.insert(entity.clone(), components_ids);
+        }
+
         entity
     }
 
+    /// Spawns every bundle yielded by `bundles` as one range of entities.
+    ///
+    /// Bundles with the same components as the one before them are filed in
+    /// the archetype of the whole run at once, so spawning many identical
+    /// entities only looks their archetype up once.
+    pub fn spawn_batch<B, I>(&mut self, bundles: I) -> Vec<Entity>
+    where
+        B: Bundle,
+        I: IntoIterator<Item = B>,
+    {
+        let bundles: Vec<B> = bundles.into_iter().collect();
+        let entities = self.entities.alloc_batch(bundles.len());
+
+        let mut run: Vec<Entity> = Vec::new();
+        let mut run_ids: Vec<ComponentId> = Vec::new();
+
+        for (index, (entity, bundle)) in entities.iter().zip(bundles).enumerate() {
+            let mut components_ids = Vec::with_capacity(run_ids.len());
+
+            bundle.components_ids(
+                entity.clone(),
+                &mut self.components,
+                &mut self.storages,
+                &mut |id| {
+                    components_ids.push(id);
+                },
+            );
+
+            if components_ids != run_ids {
+                self.insert_run(&run, &run_ids);
+                run.clear();
+
+                let remaining = entities.len() - index;
+
+                for component_id in components_ids.iter() {
+                    self.storages.reserve(component_id.clone(), remaining);
+                }
+
+                run_ids = components_ids;
+            }
+
+            run.push(entity.clone());
+        }
+
+        self.insert_run(&run, &run_ids);
+
+        entities
+    }
+
+    /// Records `entities`, all spawned with `components_ids`, and their archetype.
+    fn insert_run(&mut self, entities: &[Entity], components_ids: &[ComponentId]) {
+        if entities.is_empty() {
+            return;
+        }
+
+        self.entities.set_batch(entities, components_ids);
+        self.archetypes
+            .insert_batch(entities, components_ids.to_vec());
+    }
+
+    /// Removes `entity` and all its components, returns whether it existed.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        let Some(components_ids) = self.entities.free(&entity) else {
+            return false;
+        };
+
+        self.archetypes.remove(&entity);
+
+        for component_id in components_ids {
+            self.storages.remove(&component_id, &entity);
+        }
+
+        true
+    }
+
+    #[inline]
+    pub fn archetypes(&self) -> &Archetypes {
+        &self.archetypes
+    }
+
+    /// Inserts a global value not tied to any entity, replacing the previous one.
+    pub fn insert_resource<R: Any>(&mut self, resource: R) {
+        self.resources
+            .insert(TypeId::of::<R>(), Box::new(RefCell::new(resource)));
+    }
+
+    pub fn remove_resource<R: Any>(&mut self) -> Option<R> {
+        let resource = self.resources.remove(&TypeId::of::<R>())?;
+
+        match resource.downcast::<RefCell<R>>() {
+            Ok(resource) => Some(resource.into_inner()),
+            Err(_) => None,
+        }
+    }
+
+    pub fn contains_resource<R: Any>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    pub fn resource<R: Any>(&self) -> Option<Ref<'_, R>> {
+        self.resources
+            .get(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast_ref::<RefCell<R>>())
+            .map(|resource| resource.borrow())
+    }
+
+    pub fn resource_mut<R: Any>(&self) -> Option<RefMut<'_, R>> {
+        self.resources
+            .get(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast_ref::<RefCell<R>>())
+            .map(|resource| resource.borrow_mut())
+    }
+
     pub fn query<Q: Query>(&mut self) -> QueryState<Q> {
         QueryState::new(self)
     }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::capture(self, None)
+    }
+
+    /// Captures a snapshot sharing every storage left untouched since `previous`.
+    pub fn snapshot_from(&self, previous: &Snapshot) -> Snapshot {
+        Snapshot::capture(self, Some(previous))
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        snapshot.restore(self);
+    }
 }
 
+/// Components of one type, by entity.
+pub type ComponentStorage = HashMap<Entity, Box<RefCell<dyn Any>>>;
+
 #[derive(Debug, Default)]
 pub struct Storages {
-    pub hashmaps: HashMap<ComponentId, HashMap<Entity, Box<RefCell<dyn Any>>>>,
+    /// Indexed by component id, so queries reach their storage without hashing.
+    pub columns: Vec<ComponentStorage>,
+    change_tick: Cell<usize>,
+    changes: Vec<Cell<usize>>,
 }
 
 impl Storages {
     pub fn init_component(&mut self, component_id: ComponentId) {
-        self.hashmaps
-            .entry(component_id)
-            .or_insert_with(|| HashMap::new());
+        let len = component_id.index() + 1;
+
+        if self.columns.len() < len {
+            self.columns.resize_with(len, HashMap::new);
+            self.changes.resize_with(len, || Cell::new(0));
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, component_id: &ComponentId) -> Option<&ComponentStorage> {
+        self.columns.get(component_id.index())
+    }
+
+    /// Flags the storage as possibly modified, any mutable access counts.
+    pub fn mark_changed(&self, component_id: &ComponentId) {
+        if let Some(changed) = self.changes.get(component_id.index()) {
+            let tick = self.change_tick.get() + 1;
+
+            self.change_tick.set(tick);
+            changed.set(tick);
+        }
+    }
+
+    /// Tick of the last change made to the storage.
+    pub fn changed_at(&self, component_id: &ComponentId) -> usize {
+        self.changes
+            .get(component_id.index())
+            .map(|changed| changed.get())
+            .unwrap_or_default()
+    }
+
+    /// Puts back the change tick of a restored storage, so snapshots taken
+    /// with the same data can still be shared.
+    pub fn restore_changed_at(&mut self, component_id: &ComponentId, tick: usize) {
+        if let Some(changed) = self.changes.get(component_id.index()) {
+            changed.set(tick);
+        }
+    }
+
+    pub fn reserve(&mut self, component_id: ComponentId, additional: usize) {
+        self.init_component(component_id.clone());
+        self.columns[component_id.index()].reserve(additional);
+    }
+
+    pub fn remove(&mut self, component_id: &ComponentId, entity: &Entity) {
+        if let Some(storage) = self.columns.get_mut(component_id.index()) {
+            if storage.remove(entity).is_some() {
+                self.mark_changed(component_id);
+            }
+        }
     }
 
     pub fn push_component<C: Component + Clone>(
@@ -74,17 +272,151 @@ impl Storages {
         component_id: ComponentId,
         component: C,
     ) {
-        let component = Box::new(RefCell::new(component));
-
-        self.hashmaps
-            .entry(component_id)
-            .and_modify(|comp| {
-                comp.entry(entity.clone()).or_insert(component.clone());
-            })
-            .or_insert_with(|| {
-                let mut inner: HashMap<_, Box<RefCell<dyn Any>>> = HashMap::new();
-                inner.insert(entity, component);
-                inner
-            });
+        self.init_component(component_id.clone());
+        self.mark_changed(&component_id);
+
+        self.columns[component_id.index()]
+            .entry(entity)
+            .or_insert_with(|| Box::new(RefCell::new(component)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use breakout_macros::Component;
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Component)]
+    struct Health(u32);
+
+    #[derive(Debug, Clone, PartialEq, Component)]
+    struct Armor(u32);
+
+    #[test]
+    fn spawn_batch_allocates_one_range_of_ids() {
+        let mut world = World::new();
+
+        let first = world.spawn(Health(1));
+        let batch = world.spawn_batch((0..5).map(Health));
+
+        let ids: Vec<usize> = batch.iter().map(|entity| entity.id()).collect();
+
+        assert_eq!(ids, (first.id() + 1..first.id() + 6).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn spawn_batch_files_each_run_in_its_archetype() {
+        let mut world = World::new();
+
+        // Runs of two with armor and two without.
+        let batch = world.spawn_batch((0..8).map(|i| (Health(i), (i % 4 < 2).then_some(Armor(i)))));
+
+        let mut armored = world.query::<(Entity, &Health, &Armor)>();
+        let mut healths = world.query::<(Entity, &Health)>();
+
+        let found: Vec<(Entity, u32, u32)> = armored
+            .iter(&world)
+            .map(|(entity, health, armor)| (entity, health.unwrap().0, armor.unwrap().0))
+            .collect();
+
+        assert_eq!(found.len(), 4);
+
+        for (index, entity) in batch.iter().enumerate() {
+            let i = index as u32;
+
+            assert_eq!(
+                found.contains(&(entity.clone(), i, i)),
+                i % 4 < 2,
+                "{entity:?}"
+            );
+        }
+
+        assert_eq!(healths.iter(&world).count(), 8);
+        assert_eq!(
+            world.archetypes().location(&batch[0]),
+            world.archetypes().location(&batch[4])
+        );
+        assert_ne!(
+            world.archetypes().location(&batch[0]),
+            world.archetypes().location(&batch[2])
+        );
+    }
+
+    #[test]
+    fn despawn_removes_the_entity_once() {
+        let mut world = World::new();
+
+        let entity = world.spawn((Health(3), Armor(1)));
+
+        assert!(world.despawn(entity.clone()));
+        assert!(!world.despawn(entity.clone()));
+        assert!(!world.entities.contains(&entity));
+        assert_eq!(world.archetypes().location(&entity), None);
+    }
+
+    #[test]
+    fn despawned_ids_are_reused_with_a_new_generation() {
+        let mut world = World::new();
+
+        let first = world.spawn(Health(3));
+        let kept = world.spawn(Health(2));
+
+        world.despawn(first.clone());
+
+        let second = world.spawn(Armor(1));
+
+        assert_eq!(second.id(), first.id());
+        assert_eq!(second.generation(), first.generation() + 1);
+        assert_ne!(second, first);
+        assert_ne!(world.spawn(Armor(2)).id(), kept.id());
+    }
+
+    #[test]
+    fn batches_leave_despawned_ids_to_spawn() {
+        let mut world = World::new();
+        let mut healths = world.query::<&Health>();
+
+        let batch = world.spawn_batch((0..3).map(Health));
+
+        assert!(world.despawn(batch[1].clone()));
+        assert_eq!(healths.iter(&world).count(), 2);
+
+        let next = world.spawn_batch((0..2).map(Health));
+
+        assert_eq!(next[0].id(), batch[2].id() + 1);
+        assert_eq!(world.spawn(Health(9)).id(), batch[1].id());
+    }
+
+    #[test]
+    fn stale_handles_and_queries_skip_despawned_entities() {
+        let mut world = World::new();
+        let mut healths = world.query::<(Entity, &Health)>();
+        let mut armors = world.query::<&Armor>();
+
+        let despawned = world.spawn(Health(3));
+        let kept = world.spawn(Health(2));
+
+        assert_eq!(healths.iter(&world).count(), 2);
+
+        world.despawn(despawned.clone());
+
+        // Reuses the id of the despawned entity.
+        let reused = world.spawn((Health(5), Armor(1)));
+
+        let found: Vec<(Entity, u32)> = healths
+            .iter(&world)
+            .map(|(entity, health)| (entity, health.map(|health| health.0).unwrap()))
+            .collect();
+
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&(kept, 2)));
+        assert!(found.contains(&(reused.clone(), 5)));
+
+        let (_, health) = healths.get(&world, despawned.clone());
+
+        assert!(health.is_none());
+        assert!(armors.get(&world, despawned).is_none());
+        assert!(armors.get(&world, reused).is_some());
     }
 }
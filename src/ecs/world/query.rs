@@ -3,12 +3,15 @@ use std::any::TypeId;
 use std::cell::Ref;
 use std::cell::RefCell;
 use std::cell::RefMut;
-use std::collections::HashMap;
 
+use crate::ecs::archetype::Archetype;
+use crate::ecs::archetype::ArchetypeGeneration;
+use crate::ecs::archetype::ArchetypeId;
 use crate::ecs::component::Component;
 use crate::ecs::component::ComponentId;
 use crate::ecs::entity::Entity;
 
+use super::ComponentStorage;
 use super::World;
 
 pub trait Query {
@@ -23,17 +26,51 @@ pub trait Query {
     fn init_fetch<'w>(world: &'w World, state: &Self::State) -> Self::Fetch<'w>;
 
     fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: Entity) -> Self::Item<'w>;
+
+    fn matches_archetype(state: &Self::State, archetype: &Archetype) -> bool;
 }
 
+/// Query state meant to be kept around and reused across frames.
+///
+/// The archetypes matching the query are cached and only the ones created
+/// since the last run are checked again. The fetch state keeps the component
+/// ids, which index the world storages directly, so a fetch is built without
+/// any lookup.
 pub struct QueryState<Q: Query> {
     pub fetch_state: Q::State,
+    archetype_generation: ArchetypeGeneration,
+    matched_archetypes: Vec<ArchetypeId>,
 }
 
 impl<Q: Query> QueryState<Q> {
     pub fn new(world: &mut World) -> Self {
         let fetch_state = Q::init_state(world);
 
-        Self { fetch_state }
+        let mut state = Self {
+            fetch_state,
+            archetype_generation: ArchetypeGeneration::initial(),
+            matched_archetypes: Vec::new(),
+        };
+
+        state.update_archetypes(world);
+
+        state
+    }
+
+    pub fn update_archetypes(&mut self, world: &World) {
+        let archetypes = world.archetypes();
+
+        if self.archetype_generation == archetypes.generation() {
+            return;
+        }
+
+        for archetype in archetypes.since(self.archetype_generation) {
+            if Q::matches_archetype(&self.fetch_state, archetype) {
+                self.matched_archetypes.push(archetype.id());
+            }
+        }
+
+        self.archetype_generation = archetypes.generation();
     }
 
     pub fn get<'w>(&mut self, world: &'w World, entity: Entity) -> Q::Item<'w> {
@@ -41,6 +78,44 @@ impl<Q: Query> QueryState<Q> {
 
         Q::fetch(&mut fetch, entity)
     }
+
+    /// Iterates over every entity that has all the components of the query.
+    pub fn iter<'w, 's>(&'s mut self, world: &'w World) -> QueryIter<'w, 's, Q> {
+        self.update_archetypes(world);
+
+        QueryIter {
+            world,
+            fetch: Q::init_fetch(world, &self.fetch_state),
+            archetypes: self.matched_archetypes.iter(),
+            entities: [].iter(),
+        }
+    }
+}
+
+pub struct QueryIter<'w, 's, Q: Query> {
+    world: &'w World,
+    fetch: Q::Fetch<'w>,
+    archetypes: std::slice::Iter<'s, ArchetypeId>,
+    entities: std::slice::Iter<'w, Entity>,
+}
+
+impl<'w, 's, Q: Query> Iterator for QueryIter<'w, 's, Q> {
+    type Item = Q::Item<'w>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entity) = self.entities.next() {
+                return Some(Q::fetch(&mut self.fetch, entity.clone()));
+            }
+
+            let archetype = self.archetypes.next()?;
+
+            self.entities = match self.world.archetypes().get(*archetype) {
+                Some(archetype) => archetype.entities().iter(),
+                None => [].iter(),
+            };
+        }
+    }
 }
 
 impl Query for Entity {
@@ -57,10 +132,14 @@ impl Query for Entity {
     fn fetch<'w>(_fetch: &mut Self::Fetch<'w>, entity: Entity) -> Self::Item<'w> {
         entity
     }
+
+    fn matches_archetype(_state: &Self::State, _archetype: &Archetype) -> bool {
+        true
+    }
 }
 
 pub struct ReadFetch<'a> {
-    storage: Option<&'a HashMap<Entity, Box<RefCell<dyn Any>>>>,
+    storage: Option<&'a ComponentStorage>,
 }
 
 fn downcast_ref<'w, T: Any>(cell: &'w Box<RefCell<dyn Any>>) -> Option<Ref<'w, T>> {
@@ -96,7 +175,7 @@ impl<T: Component> Query for &T {
 
     fn init_fetch<'w>(world: &'w World, component_id: &ComponentId) -> Self::Fetch<'w> {
         ReadFetch {
-            storage: world.storages.hashmaps.get(component_id),
+            storage: world.storages.get(component_id),
         }
     }
 
@@ -109,6 +188,10 @@ impl<T: Component> Query for &T {
             None => None,
         }
     }
+
+    fn matches_archetype(component_id: &ComponentId, archetype: &Archetype) -> bool {
+        archetype.contains(component_id)
+    }
 }
 
 impl<T: Component> Query for &mut T {
@@ -123,8 +206,10 @@ impl<T: Component> Query for &mut T {
     }
 
     fn init_fetch<'w>(world: &'w World, component_id: &ComponentId) -> Self::Fetch<'w> {
+        world.storages.mark_changed(component_id);
+
         ReadFetch {
-            storage: world.storages.hashmaps.get(component_id),
+            storage: world.storages.get(component_id),
         }
     }
 
@@ -137,6 +222,10 @@ impl<T: Component> Query for &mut T {
             None => None,
         }
     }
+
+    fn matches_archetype(component_id: &ComponentId, archetype: &Archetype) -> bool {
+        archetype.contains(component_id)
+    }
 }
 
 macro_rules! tuple_impls {
@@ -170,8 +259,72 @@ macro_rules! tuple_impl {
                 let ($($name,)*) = _fetch;
                 ($($name::fetch($name, _entity.clone()),)*)
             }
+
+            fn matches_archetype(state: &Self::State, _archetype: &Archetype) -> bool {
+                let ($($name,)*) = state;
+                $($name::matches_archetype($name, _archetype))&&*
+            }
         }
     };
 }
 
 tuple_impls!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+#[cfg(test)]
+mod tests {
+    use breakout_macros::Component;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Component)]
+    struct Health(u32);
+
+    #[derive(Debug, Clone, Component)]
+    struct Armor;
+
+    #[test]
+    fn picks_up_archetypes_created_after_the_first_run() {
+        let mut world = World::new();
+        let mut healths = world.query::<&Health>();
+
+        world.spawn(Health(1));
+
+        assert_eq!(healths.iter(&world).count(), 1);
+
+        world.spawn((Health(2), Armor));
+        world.spawn(Armor);
+
+        let mut found: Vec<u32> = healths
+            .iter(&world)
+            .map(|health| health.unwrap().0)
+            .collect();
+        found.sort();
+
+        assert_eq!(found, vec![1, 2]);
+    }
+
+    #[test]
+    fn only_checks_archetypes_once() {
+        let mut world = World::new();
+        let mut healths = world.query::<&Health>();
+
+        world.spawn(Health(1));
+        world.spawn((Health(2), Armor));
+        world.spawn(Armor);
+
+        healths.update_archetypes(&world);
+
+        assert_eq!(
+            healths.archetype_generation,
+            world.archetypes().generation()
+        );
+        assert_eq!(healths.matched_archetypes.len(), 2);
+
+        // Same archetypes, nothing new to match.
+        world.spawn(Health(3));
+        healths.update_archetypes(&world);
+
+        assert_eq!(healths.matched_archetypes.len(), 2);
+        assert_eq!(healths.iter(&world).count(), 3);
+    }
+}
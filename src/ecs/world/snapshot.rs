@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ecs::component::CloneFn;
+use crate::ecs::component::ComponentId;
+use crate::ecs::entity::Entities;
+use crate::ecs::entity::Entity;
+
+use super::ComponentStorage;
+use super::World;
+
+#[derive(Debug, Clone)]
+struct StorageSnapshot {
+    changed_at: usize,
+    clone: CloneFn,
+    components: Rc<ComponentStorage>,
+}
+
+/// Copy of the entities and registered components of a [`World`].
+///
+/// Cloning a snapshot is cheap, and storages that did not change between two
+/// snapshots are shared instead of copied.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    entities: Rc<Entities>,
+    storages: HashMap<ComponentId, StorageSnapshot>,
+}
+
+impl Snapshot {
+    pub(super) fn capture(world: &World, previous: Option<&Snapshot>) -> Snapshot {
+        let mut storages = HashMap::new();
+
+        for info in world.components.iter() {
+            let Some(clone) = info.clone else {
+                continue;
+            };
+
+            let changed_at = world.storages.changed_at(&info.id);
+
+            let shared = previous
+                .and_then(|previous| previous.storages.get(&info.id))
+                .filter(|storage| storage.changed_at == changed_at)
+                .cloned();
+
+            let storage = match shared {
+                Some(storage) => storage,
+                None => StorageSnapshot {
+                    changed_at,
+                    clone,
+                    components: Rc::new(match world.storages.get(&info.id) {
+                        Some(storage) => clone_storage(storage, clone),
+                        None => HashMap::new(),
+                    }),
+                },
+            };
+
+            storages.insert(info.id.clone(), storage);
+        }
+
+        Snapshot {
+            entities: Rc::new(world.entities.clone()),
+            storages,
+        }
+    }
+
+    pub(super) fn restore(&self, world: &mut World) {
+        world.entities = (*self.entities).clone();
+
+        for (index, storage) in world.storages.columns.iter_mut().enumerate() {
+            match self.storages.get(&ComponentId::from(index)) {
+                Some(snapshot) => *storage = clone_storage(&snapshot.components, snapshot.clone),
+                // Components not registered for snapshots are kept, minus the
+                // ones belonging to entities that did not exist back then.
+                None => storage.retain(|entity, _| world.entities.contains(entity)),
+            }
+        }
+
+        for (component_id, snapshot) in self.storages.iter() {
+            world
+                .storages
+                .restore_changed_at(component_id, snapshot.changed_at);
+        }
+
+        // Entities despawned since the capture come back without the components
+        // that were not snapshotted, so their archetype is rebuilt from the
+        // storages still holding them.
+        world.archetypes.clear_entities();
+
+        let entities: Vec<Entity> = world.entities.iter().cloned().collect();
+
+        for entity in entities {
+            let components_ids: Vec<ComponentId> = world
+                .storages
+                .columns
+                .iter()
+                .enumerate()
+                .filter(|(_, storage)| storage.contains_key(&entity))
+                .map(|(index, _)| ComponentId::from(index))
+                .collect();
+
+            world
+                .entities
+                .replace_components(entity.clone(), components_ids.clone());
+            world.archetypes.insert(entity, components_ids);
+        }
+    }
+}
+
+fn clone_storage(storage: &ComponentStorage, clone: CloneFn) -> ComponentStorage {
+    storage
+        .iter()
+        .map(|(entity, component)| (entity.clone(), clone(&*component.borrow())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use breakout_macros::Component;
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Component)]
+    struct Health(u32);
+
+    #[derive(Debug, Clone, PartialEq, Component)]
+    struct Armor(u32);
+
+    /// Never snapshotted.
+    #[derive(Debug, Clone, PartialEq, Component)]
+    struct Tag;
+
+    #[test]
+    fn restores_despawned_entities_without_their_other_components() {
+        let mut world = World::new();
+        let health = world.register_snapshot::<Health>();
+        let mut healths = world.query::<(Entity, &Health)>();
+        let mut tags = world.query::<&Tag>();
+
+        let entity = world.spawn((Health(3), Tag));
+        let snapshot = world.snapshot();
+
+        world.despawn(entity.clone());
+        world.restore(&snapshot);
+
+        let found: Vec<(Entity, u32)> = healths
+            .iter(&world)
+            .map(|(entity, health)| (entity, health.unwrap().0))
+            .collect();
+
+        assert_eq!(found, vec![(entity.clone(), 3)]);
+        assert_eq!(tags.iter(&world).count(), 0);
+
+        let archetype = world.archetypes().location(&entity).unwrap();
+
+        assert_eq!(
+            world.archetypes().get(archetype).unwrap().components(),
+            &[health]
+        );
+    }
+
+    #[test]
+    fn restores_around_components_registered_after_the_capture() {
+        let mut world = World::new();
+        world.register_snapshot::<Health>();
+
+        let kept = world.spawn(Health(3));
+        let snapshot = world.snapshot();
+
+        world.register_snapshot::<Armor>();
+        world.spawn((Health(2), Armor(1)));
+        world.spawn(Tag);
+
+        world.restore(&snapshot);
+
+        let mut healths = world.query::<(Entity, &Health)>();
+        let mut armors = world.query::<&Armor>();
+        let mut tags = world.query::<&Tag>();
+
+        let found: Vec<Entity> = healths.iter(&world).map(|(entity, _)| entity).collect();
+
+        assert_eq!(found, vec![kept]);
+        assert_eq!(armors.iter(&world).count(), 0);
+        assert_eq!(tags.iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn shares_unchanged_storages() {
+        let mut world = World::new();
+        let health = world.register_snapshot::<Health>();
+        let armor = world.register_snapshot::<Armor>();
+
+        let entity = world.spawn((Health(3), Armor(1)));
+        let first = world.snapshot();
+
+        if let Some(mut health) = world.query::<&mut Health>().get(&world, entity.clone()) {
+            health.0 = 2;
+        }
+
+        let second = world.snapshot_from(&first);
+
+        assert!(Rc::ptr_eq(
+            &first.storages[&armor].components,
+            &second.storages[&armor].components
+        ));
+        assert!(!Rc::ptr_eq(
+            &first.storages[&health].components,
+            &second.storages[&health].components
+        ));
+
+        world.restore(&first);
+
+        assert_eq!(
+            world
+                .query::<&Health>()
+                .get(&world, entity)
+                .map(|health| health.0),
+            Some(3)
+        );
+    }
+}
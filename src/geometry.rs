@@ -1,26 +1,1294 @@
-use ggez::glam::Vec2;
+use std::f32::consts::TAU;
 
-/// Returns (x, y, w, h)
-pub fn intersection(
-    pos_a: Vec2,
-    dim_a_w: f32,
-    dim_a_h: f32,
-    pos_b: Vec2,
-    dim_b_w: f32,
-    dim_b_h: f32,
-) -> Option<(f32, f32, f32, f32)> {
-    let left = pos_a.x.max(pos_b.x);
-    let top = pos_a.y.max(pos_b.y);
+use crate::math::vec2;
+use crate::math::Real;
+use crate::math::Scalar;
+use crate::math::Vector;
 
-    let right = (pos_a.x + dim_a_w).min(pos_b.x + dim_b_w);
-    let bottom = (pos_a.y + dim_a_h).min(pos_b.y + dim_b_h);
+/// Scales, then rotates, then translates points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    pub translation: Vector,
+    /// Counterclockwise angle in radians, which turns clockwise on screen
+    /// since y points down.
+    pub rotation: Real,
+    pub scale: Vector,
+}
+
+impl Transform2D {
+    pub const IDENTITY: Transform2D = Transform2D {
+        translation: Vector::ZERO,
+        rotation: Real::ZERO,
+        scale: Vector::ONE,
+    };
+
+    #[inline]
+    pub fn new(
+        translation: impl Into<Vector>,
+        rotation: impl Into<Real>,
+        scale: impl Into<Vector>,
+    ) -> Transform2D {
+        Transform2D {
+            translation: translation.into(),
+            rotation: rotation.into(),
+            scale: scale.into(),
+        }
+    }
+
+    #[inline]
+    pub fn from_translation(translation: impl Into<Vector>) -> Transform2D {
+        Transform2D {
+            translation: translation.into(),
+            ..Transform2D::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub fn from_rotation(rotation: impl Into<Real>) -> Transform2D {
+        Transform2D {
+            rotation: rotation.into(),
+            ..Transform2D::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub fn from_scale(scale: impl Into<Vector>) -> Transform2D {
+        Transform2D {
+            scale: scale.into(),
+            ..Transform2D::IDENTITY
+        }
+    }
+
+    /// Whether the transform only moves things around.
+    #[inline]
+    pub fn is_translation(&self) -> bool {
+        self.rotation == Real::ZERO && self.scale == Vector::ONE
+    }
+
+    #[inline]
+    pub fn transform_point(&self, point: Vector) -> Vector {
+        self.transform_vector(point) + self.translation
+    }
+
+    /// Like [`Transform2D::transform_point`], ignoring the translation.
+    #[inline]
+    pub fn transform_vector(&self, vector: Vector) -> Vector {
+        Vector::from_angle(self.rotation).rotate(vector * self.scale)
+    }
+
+    #[inline]
+    pub fn inverse_transform_point(&self, point: Vector) -> Vector {
+        self.inverse_transform_vector(point - self.translation)
+    }
+
+    #[inline]
+    pub fn inverse_transform_vector(&self, vector: Vector) -> Vector {
+        Vector::from_angle(-self.rotation).rotate(vector) / self.scale
+    }
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Transform2D::IDENTITY
+    }
+}
+
+/// Axis aligned rectangle, `min` being its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl Aabb {
+    #[inline]
+    pub fn new(
+        position: impl Into<Vector>,
+        width: impl Into<Real>,
+        height: impl Into<Real>,
+    ) -> Aabb {
+        let position = position.into();
+
+        Aabb {
+            min: position,
+            max: position + vec2(width, height),
+        }
+    }
+
+    #[inline]
+    pub fn from_center(center: impl Into<Vector>, half_extents: impl Into<Vector>) -> Aabb {
+        let (center, half_extents) = (center.into(), half_extents.into());
+
+        Aabb {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector {
+        self.max - self.min
+    }
+
+    #[inline]
+    pub fn width(&self) -> Real {
+        self.max.x - self.min.x
+    }
+
+    #[inline]
+    pub fn height(&self) -> Real {
+        self.max.y - self.min.y
+    }
+
+    #[inline]
+    pub fn center(&self) -> Vector {
+        (self.min + self.max) * 0.5
+    }
+
+    #[inline]
+    pub fn translate(&self, offset: Vector) -> Aabb {
+        Aabb {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Grows the rectangle by `amount` on every side.
+    #[inline]
+    pub fn expand(&self, amount: Vector) -> Aabb {
+        Aabb {
+            min: self.min - amount,
+            max: self.max + amount,
+        }
+    }
+
+    /// Grows the rectangle by `amount` on every side, shrinking it when negative.
+    #[inline]
+    pub fn inflate(&self, amount: Real) -> Aabb {
+        self.expand(Vector::splat(amount))
+    }
+
+    #[inline]
+    pub fn contains(&self, point: Vector) -> bool {
+        point.x >= self.min.x
+            && point.y >= self.min.y
+            && point.x <= self.max.x
+            && point.y <= self.max.y
+    }
+
+    #[inline]
+    pub fn contains_rect(&self, other: &Aabb) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    #[inline]
+    pub fn closest_point(&self, point: Vector) -> Vector {
+        point.clamp(self.min, self.max)
+    }
+
+    #[inline]
+    pub fn distance(&self, point: Vector) -> Real {
+        self.closest_point(point).distance(point)
+    }
+
+    /// The part of the rectangle inside `bounds`, squashed to an edge of
+    /// `bounds` when they do not overlap.
+    pub fn clip(&self, bounds: &Aabb) -> Aabb {
+        Aabb {
+            min: bounds.closest_point(self.min),
+            max: bounds.closest_point(self.max),
+        }
+    }
+
+    /// Cuts the rectangle in two at `x`, left part first.
+    pub fn split_x(&self, x: Real) -> (Aabb, Aabb) {
+        let x = x.clamp(self.min.x, self.max.x);
+
+        (
+            Aabb {
+                min: self.min,
+                max: vec2(x, self.max.y),
+            },
+            Aabb {
+                min: vec2(x, self.min.y),
+                max: self.max,
+            },
+        )
+    }
+
+    /// Cuts the rectangle in two at `y`, top part first.
+    pub fn split_y(&self, y: Real) -> (Aabb, Aabb) {
+        let y = y.clamp(self.min.y, self.max.y);
+
+        (
+            Aabb {
+                min: self.min,
+                max: vec2(self.max.x, y),
+            },
+            Aabb {
+                min: vec2(self.min.x, y),
+                max: self.max,
+            },
+        )
+    }
+
+    /// Smallest rectangle containing both rectangles.
+    #[inline]
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn intersection(&self, other: &Aabb) -> Option<Aabb> {
+        let min = self.min.max(other.min);
+        let max = self.max.min(other.max);
+
+        if max.x < min.x || max.y < min.y {
+            return None;
+        }
+
+        Some(Aabb { min, max })
+    }
+}
+
+/// Rectangle rotated around its center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Obb {
+    pub center: Vector,
+    pub half_extents: Vector,
+    /// Angle in radians, see [`Transform2D::rotation`].
+    pub rotation: Real,
+}
+
+impl Obb {
+    #[inline]
+    pub fn new(
+        center: impl Into<Vector>,
+        half_extents: impl Into<Vector>,
+        rotation: impl Into<Real>,
+    ) -> Obb {
+        Obb {
+            center: center.into(),
+            half_extents: half_extents.into(),
+            rotation: rotation.into(),
+        }
+    }
+
+    /// The local x and y axes of the rectangle.
+    #[inline]
+    pub fn axes(&self) -> [Vector; 2] {
+        let x = Vector::from_angle(self.rotation);
+
+        [x, x.perp()]
+    }
+
+    pub fn vertices(&self) -> [Vector; 4] {
+        let [x, y] = self.axes();
+        let (x, y) = (x * self.half_extents.x, y * self.half_extents.y);
+
+        [
+            self.center - x - y,
+            self.center + x - y,
+            self.center + x + y,
+            self.center - x + y,
+        ]
+    }
+
+    pub fn contains(&self, point: Vector) -> bool {
+        let local = Vector::from_angle(-self.rotation).rotate(point - self.center);
+
+        let local = local.abs();
+
+        local.x <= self.half_extents.x && local.y <= self.half_extents.y
+    }
+
+    #[inline]
+    pub fn translate(&self, offset: Vector) -> Obb {
+        Obb {
+            center: self.center + offset,
+            ..*self
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        let [x, y] = self.axes();
+        let extents = (x * self.half_extents.x).abs() + (y * self.half_extents.y).abs();
+
+        Aabb {
+            min: self.center - extents,
+            max: self.center + extents,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Vector,
+    pub radius: Real,
+}
+
+impl Circle {
+    #[inline]
+    pub fn new(center: impl Into<Vector>, radius: impl Into<Real>) -> Circle {
+        Circle {
+            center: center.into(),
+            radius: radius.into(),
+        }
+    }
+
+    #[inline]
+    pub fn translate(&self, offset: Vector) -> Circle {
+        Circle {
+            center: self.center + offset,
+            radius: self.radius,
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, point: Vector) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    /// Closest point of the disc, `point` itself when inside.
+    pub fn closest_point(&self, point: Vector) -> Vector {
+        if self.contains(point) {
+            return point;
+        }
+
+        self.center + (point - self.center).normalize_or_zero() * self.radius
+    }
+
+    #[inline]
+    pub fn distance(&self, point: Vector) -> Real {
+        (self.center.distance(point) - self.radius).max(Real::ZERO)
+    }
+
+    #[inline]
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_center(self.center, Vector::splat(self.radius))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub start: Vector,
+    pub end: Vector,
+}
+
+impl Segment {
+    #[inline]
+    pub fn new(start: impl Into<Vector>, end: impl Into<Vector>) -> Segment {
+        Segment {
+            start: start.into(),
+            end: end.into(),
+        }
+    }
+
+    /// From `start` to `end`, not normalized.
+    #[inline]
+    pub fn direction(&self) -> Vector {
+        self.end - self.start
+    }
+
+    #[inline]
+    pub fn length(&self) -> Real {
+        self.direction().length()
+    }
+
+    #[inline]
+    pub fn translate(&self, offset: Vector) -> Segment {
+        Segment {
+            start: self.start + offset,
+            end: self.end + offset,
+        }
+    }
+
+    #[inline]
+    pub fn bounds(&self) -> Aabb {
+        Aabb {
+            min: self.start.min(self.end),
+            max: self.start.max(self.end),
+        }
+    }
+
+    pub fn closest_point(&self, point: Vector) -> Vector {
+        let direction = self.direction();
+        let length_squared = direction.length_squared();
+
+        if length_squared == 0.0 {
+            return self.start;
+        }
+
+        // Divided last, so points on the grid stay there with fixed point numbers.
+        let along = (point - self.start)
+            .dot(direction)
+            .clamp(Real::ZERO, length_squared);
+
+        self.start + direction * along / length_squared
+    }
+
+    #[inline]
+    pub fn distance(&self, point: Vector) -> Real {
+        self.closest_point(point).distance(point)
+    }
+
+    /// Where both segments cross. Collinear segments overlapping each other
+    /// meet at the point of the overlap closest to `start`.
+    pub fn intersection(&self, other: &Segment) -> Option<Vector> {
+        let (a, b) = (self.direction(), other.direction());
+        let denominator = a.perp_dot(b);
+        let offset = other.start - self.start;
+
+        if denominator == 0.0 {
+            return self.overlap(other);
+        }
+
+        let t = offset.perp_dot(b) / denominator;
+        let u = offset.perp_dot(a) / denominator;
+
+        if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        Some(self.start + a * offset.perp_dot(b) / denominator)
+    }
+
+    /// Start of the overlap of two parallel segments, `None` unless collinear.
+    fn overlap(&self, other: &Segment) -> Option<Vector> {
+        let direction = self.direction();
+        let length_squared = direction.length_squared();
+
+        if length_squared == 0.0 {
+            return (other.distance(self.start) == 0.0).then_some(self.start);
+        }
+
+        if (other.start - self.start).perp_dot(direction) != 0.0
+            || (other.end - self.start).perp_dot(direction) != 0.0
+        {
+            return None;
+        }
+
+        // Scaled by the squared length, divided only once the point is known.
+        let along = |point: Vector| (point - self.start).dot(direction);
+        let (start, end) = (along(other.start), along(other.end));
+
+        let first = start.min(end).max(Real::ZERO);
+        let last = start.max(end).min(length_squared);
+
+        if first > last {
+            return None;
+        }
+
+        Some(self.start + direction * first / length_squared)
+    }
+}
+
+/// Half line starting at `origin`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vector,
+    /// Always normalized.
+    pub direction: Vector,
+}
+
+impl Ray {
+    #[inline]
+    pub fn new(origin: impl Into<Vector>, direction: impl Into<Vector>) -> Ray {
+        Ray {
+            origin: origin.into(),
+            direction: direction.into().normalize_or_zero(),
+        }
+    }
+
+    /// The point `distance` along the ray.
+    #[inline]
+    pub fn at(&self, distance: Real) -> Vector {
+        self.origin + self.direction * distance
+    }
+
+    /// Distance to where the ray enters `aabb`, 0 when starting inside.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<Real> {
+        let mut entry = Real::ZERO;
+        let mut exit = Real::INFINITY;
+
+        for axis in 0..2 {
+            if self.direction[axis] == 0.0 {
+                if self.origin[axis] < aabb.min[axis] || self.origin[axis] > aabb.max[axis] {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let near = (aabb.min[axis] - self.origin[axis]) / self.direction[axis];
+            let far = (aabb.max[axis] - self.origin[axis]) / self.direction[axis];
+
+            entry = entry.max(near.min(far));
+            exit = exit.min(near.max(far));
+        }
+
+        if entry > exit {
+            return None;
+        }
+
+        Some(entry)
+    }
+
+    /// Distance to where the ray enters `circle`, 0 when starting inside.
+    pub fn intersect_circle(&self, circle: &Circle) -> Option<Real> {
+        if circle.contains(self.origin) {
+            return Some(Real::ZERO);
+        }
+
+        let offset = self.origin - circle.center;
+        let along = offset.dot(self.direction);
+        let discriminant = along * along - offset.length_squared() + circle.radius * circle.radius;
+
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let distance = -along - discriminant.sqrt();
+
+        if distance < 0.0 {
+            return None;
+        }
+
+        Some(distance)
+    }
+
+    /// Distance to where the ray crosses `segment`.
+    pub fn intersect_segment(&self, segment: &Segment) -> Option<Real> {
+        let direction = segment.direction();
+        let denominator = self.direction.perp_dot(direction);
+
+        if denominator == 0.0 {
+            return None;
+        }
+
+        let offset = segment.start - self.origin;
+        let distance = offset.perp_dot(direction) / denominator;
+        let along = offset.perp_dot(self.direction) / denominator;
+
+        if distance < 0.0 || !(0.0..=1.0).contains(&along) {
+            return None;
+        }
+
+        Some(distance)
+    }
+}
+
+/// Convex polygon with up to [`Polygon::MAX_VERTICES`] vertices, stored inline
+/// so shapes stay `Copy`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Polygon {
+    vertices: [Vector; Polygon::MAX_VERTICES],
+    len: usize,
+}
+
+impl Polygon {
+    pub const MAX_VERTICES: usize = 8;
+
+    /// Vertices have to go around a convex polygon, in either direction.
+    ///
+    /// Returns `None` with fewer than 3 or more than [`Polygon::MAX_VERTICES`]
+    /// vertices, or when they do not make a convex polygon turning the same
+    /// way at every vertex.
+    pub fn new(vertices: &[Vector]) -> Option<Polygon> {
+        if vertices.len() < 3 || vertices.len() > Polygon::MAX_VERTICES {
+            return None;
+        }
+
+        if !Polygon::is_convex(vertices) {
+            return None;
+        }
+
+        Some(Polygon::new_unchecked(vertices))
+    }
+
+    fn new_unchecked(vertices: &[Vector]) -> Polygon {
+        let mut polygon = Polygon {
+            vertices: [Vector::ZERO; Polygon::MAX_VERTICES],
+            len: vertices.len(),
+        };
+
+        polygon.vertices[..vertices.len()].copy_from_slice(vertices);
+        polygon
+    }
+
+    /// Whether every vertex turns the same way, going around only once.
+    fn is_convex(vertices: &[Vector]) -> bool {
+        let len = vertices.len();
+        let mut sign = Real::ZERO;
+        let mut turned = Real::ZERO;
+
+        for i in 0..len {
+            let edge = vertices[(i + 1) % len] - vertices[i];
+            let next = vertices[(i + 2) % len] - vertices[(i + 1) % len];
+            let cross = edge.perp_dot(next);
+
+            // Repeated or aligned vertices leave an edge without a normal.
+            if !cross.is_finite() || cross == 0.0 {
+                return false;
+            }
+
+            if sign == 0.0 {
+                sign = cross.signum();
+            } else if cross.signum() != sign {
+                return false;
+            }
 
-    // (x, y, w, h)
-    let intersection = (left, top, right - left, bottom - top);
+            turned += cross.atan2(edge.dot(next));
+        }
 
-    if right < left || bottom < top {
-        return None;
+        // A star also turns the same way everywhere, but goes around twice.
+        // The turns add up to whole turns, give or take the rounding.
+        turned.abs() < 1.5 * TAU
     }
 
-    Some(intersection)
+    #[inline]
+    pub fn vertices(&self) -> &[Vector] {
+        &self.vertices[..self.len]
+    }
+
+    /// Average of the vertices, always inside a convex polygon.
+    pub fn center(&self) -> Vector {
+        self.vertices().iter().sum::<Vector>() / self.len as f32
+    }
+
+    /// Each edge as its start and end vertices.
+    pub fn edges(&self) -> impl Iterator<Item = (Vector, Vector)> + '_ {
+        let vertices = self.vertices();
+
+        (0..vertices.len()).map(move |i| (vertices[i], vertices[(i + 1) % vertices.len()]))
+    }
+
+    /// Outward facing unit normal of each edge.
+    pub fn normals(&self) -> impl Iterator<Item = Vector> + '_ {
+        let center = self.center();
+
+        self.edges().map(move |(start, end)| {
+            let edge = end - start;
+            let normal = vec2(edge.y, -edge.x).normalize_or_zero();
+
+            if (start - center).dot(normal) < 0.0 {
+                -normal
+            } else {
+                normal
+            }
+        })
+    }
+
+    /// Smallest and largest distance of the vertices along `axis`.
+    pub fn project(&self, axis: Vector) -> (Real, Real) {
+        self.vertices().iter().map(|vertex| vertex.dot(axis)).fold(
+            (Real::INFINITY, Real::NEG_INFINITY),
+            |(min, max), distance| (min.min(distance), max.max(distance)),
+        )
+    }
+
+    pub fn contains(&self, point: Vector) -> bool {
+        self.edges()
+            .zip(self.normals())
+            .all(|((start, _), normal)| (point - start).dot(normal) <= 0.0)
+    }
+
+    /// Closest point of the polygon, `point` itself when inside.
+    pub fn closest_point(&self, point: Vector) -> Vector {
+        if self.contains(point) {
+            return point;
+        }
+
+        self.edges()
+            .map(|(start, end)| Segment::new(start, end).closest_point(point))
+            .min_by(|a, b| {
+                a.distance_squared(point)
+                    .total_cmp(&b.distance_squared(point))
+            })
+            .unwrap_or(point)
+    }
+
+    #[inline]
+    pub fn distance(&self, point: Vector) -> Real {
+        self.closest_point(point).distance(point)
+    }
+
+    /// Vertex furthest along `direction`.
+    pub fn support(&self, direction: Vector) -> Vector {
+        self.vertices()
+            .iter()
+            .copied()
+            .max_by(|a, b| a.dot(direction).total_cmp(&b.dot(direction)))
+            .unwrap_or_default()
+    }
+
+    pub fn translate(&self, offset: Vector) -> Polygon {
+        let mut polygon = *self;
+
+        for vertex in polygon.vertices[..self.len].iter_mut() {
+            *vertex += offset;
+        }
+
+        polygon
+    }
+
+    pub fn transform(&self, transform: &Transform2D) -> Polygon {
+        let mut polygon = *self;
+
+        for vertex in polygon.vertices[..self.len].iter_mut() {
+            *vertex = transform.transform_point(*vertex);
+        }
+
+        polygon
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        let vertices = self.vertices();
+
+        Aabb {
+            min: vertices.iter().copied().fold(vertices[0], Vector::min),
+            max: vertices.iter().copied().fold(vertices[0], Vector::max),
+        }
+    }
+}
+
+impl From<Obb> for Polygon {
+    fn from(value: Obb) -> Self {
+        Polygon::new_unchecked(&value.vertices())
+    }
+}
+
+impl From<Aabb> for Polygon {
+    fn from(value: Aabb) -> Self {
+        Polygon::new_unchecked(&[
+            value.min,
+            vec2(value.max.x, value.min.y),
+            value.max,
+            vec2(value.min.x, value.max.y),
+        ])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Aabb(Aabb),
+    Circle(Circle),
+    Polygon(Polygon),
+}
+
+impl Shape {
+    pub fn translate(&self, offset: Vector) -> Shape {
+        match self {
+            Shape::Aabb(aabb) => Shape::Aabb(aabb.translate(offset)),
+            Shape::Circle(circle) => Shape::Circle(circle.translate(offset)),
+            Shape::Polygon(polygon) => Shape::Polygon(polygon.translate(offset)),
+        }
+    }
+
+    /// The shape with `transform` applied, boxes becoming polygons once rotated.
+    /// Circles keep their shape, growing by the largest scale.
+    pub fn transform(&self, transform: &Transform2D) -> Shape {
+        if transform.is_translation() {
+            return self.translate(transform.translation);
+        }
+
+        match self {
+            Shape::Aabb(aabb) if transform.rotation == Real::ZERO => {
+                let (a, b) = (
+                    transform.transform_point(aabb.min),
+                    transform.transform_point(aabb.max),
+                );
+
+                Shape::Aabb(Aabb {
+                    min: a.min(b),
+                    max: a.max(b),
+                })
+            }
+            Shape::Aabb(aabb) => Polygon::from(*aabb).transform(transform).into(),
+            Shape::Circle(circle) => Shape::Circle(Circle {
+                center: transform.transform_point(circle.center),
+                radius: circle.radius * transform.scale.abs().max_element(),
+            }),
+            Shape::Polygon(polygon) => Shape::Polygon(polygon.transform(transform)),
+        }
+    }
+
+    /// Smallest rectangle containing the whole shape.
+    pub fn bounds(&self) -> Aabb {
+        match self {
+            Shape::Aabb(aabb) => *aabb,
+            Shape::Circle(circle) => circle.bounds(),
+            Shape::Polygon(polygon) => polygon.bounds(),
+        }
+    }
+
+    pub fn contains(&self, point: Vector) -> bool {
+        match self {
+            Shape::Aabb(aabb) => aabb.contains(point),
+            Shape::Circle(circle) => circle.contains(point),
+            Shape::Polygon(polygon) => polygon.contains(point),
+        }
+    }
+
+    /// Closest point of the shape, `point` itself when inside.
+    pub fn closest_point(&self, point: Vector) -> Vector {
+        match self {
+            Shape::Aabb(aabb) => aabb.closest_point(point),
+            Shape::Circle(circle) => circle.closest_point(point),
+            Shape::Polygon(polygon) => polygon.closest_point(point),
+        }
+    }
+
+    /// Distance from `point` to the shape, 0 when inside.
+    #[inline]
+    pub fn distance(&self, point: Vector) -> Real {
+        self.closest_point(point).distance(point)
+    }
+}
+
+impl From<Aabb> for Shape {
+    fn from(value: Aabb) -> Self {
+        Shape::Aabb(value)
+    }
+}
+
+impl From<Circle> for Shape {
+    fn from(value: Circle) -> Self {
+        Shape::Circle(value)
+    }
+}
+
+impl From<Obb> for Shape {
+    fn from(value: Obb) -> Self {
+        Shape::Polygon(value.into())
+    }
+}
+
+impl From<Polygon> for Shape {
+    fn from(value: Polygon) -> Self {
+        Shape::Polygon(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math;
+
+    use super::*;
+
+    /// Loose enough for the fixed point trigonometry of deterministic builds.
+    const EPSILON: f32 = 1e-3;
+
+    fn assert_near(actual: Vector, expected: Vector) {
+        assert!(
+            actual.abs_diff_eq(expected, EPSILON),
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    fn rect() -> Aabb {
+        Aabb::new(vec2(10.0, 20.0), 30.0, 40.0)
+    }
+
+    #[test]
+    fn rect_contains_its_edges_only() {
+        let rect = rect();
+
+        assert!(rect.contains(vec2(25.0, 40.0)));
+        assert!(rect.contains(rect.min));
+        assert!(rect.contains(rect.max));
+        assert!(!rect.contains(vec2(9.9, 40.0)));
+        assert!(!rect.contains(vec2(25.0, 60.1)));
+        assert!(rect.contains_rect(&Aabb::new(vec2(15.0, 25.0), 5.0, 5.0)));
+        assert!(!rect.contains_rect(&Aabb::new(vec2(35.0, 25.0), 10.0, 5.0)));
+    }
+
+    #[test]
+    fn rect_union_covers_both() {
+        let other = Aabb::new(vec2(-5.0, 50.0), 10.0, 30.0);
+
+        assert_eq!(
+            rect().union(&other),
+            Aabb {
+                min: vec2(-5.0, 20.0),
+                max: vec2(40.0, 80.0),
+            }
+        );
+        assert_eq!(rect().union(&rect()), rect());
+    }
+
+    #[test]
+    fn rect_inflate_grows_and_shrinks_around_the_center() {
+        let grown = rect().inflate(math::real(5.0));
+        let shrunk = rect().inflate(math::real(-5.0));
+
+        assert_eq!(grown, Aabb::new(vec2(5.0, 15.0), 40.0, 50.0));
+        assert_eq!(shrunk, Aabb::new(vec2(15.0, 25.0), 20.0, 30.0));
+        assert_eq!(grown.center(), rect().center());
+    }
+
+    #[test]
+    fn rect_clip_keeps_the_part_inside() {
+        let bounds = Aabb::new(vec2(0.0, 0.0), 30.0, 30.0);
+
+        assert_eq!(
+            rect().clip(&bounds),
+            Aabb::new(vec2(10.0, 20.0), 20.0, 10.0)
+        );
+        assert_eq!(bounds.clip(&bounds), bounds);
+
+        let outside = Aabb::new(vec2(50.0, 5.0), 10.0, 10.0).clip(&bounds);
+
+        assert_eq!(outside.width(), 0.0);
+        assert_eq!(outside.min.x, bounds.max.x);
+    }
+
+    #[test]
+    fn rect_split_cuts_in_two() {
+        let (left, right) = rect().split_x(math::real(25.0));
+
+        assert_eq!(left, Aabb::new(vec2(10.0, 20.0), 15.0, 40.0));
+        assert_eq!(right, Aabb::new(vec2(25.0, 20.0), 15.0, 40.0));
+
+        let (top, bottom) = rect().split_y(math::real(30.0));
+
+        assert_eq!(top, Aabb::new(vec2(10.0, 20.0), 30.0, 10.0));
+        assert_eq!(bottom, Aabb::new(vec2(10.0, 30.0), 30.0, 30.0));
+    }
+
+    #[test]
+    fn rect_split_outside_leaves_an_empty_part() {
+        let (left, right) = rect().split_x(math::real(100.0));
+
+        assert_eq!(left, rect());
+        assert_eq!(right.width(), 0.0);
+
+        let (top, bottom) = rect().split_y(math::real(-100.0));
+
+        assert_eq!(top.height(), 0.0);
+        assert_eq!(bottom, rect());
+    }
+
+    #[test]
+    fn rect_intersection() {
+        let other = Aabb::new(vec2(30.0, 50.0), 30.0, 30.0);
+
+        assert_eq!(
+            rect().intersection(&other),
+            Some(Aabb::new(vec2(30.0, 50.0), 10.0, 10.0))
+        );
+        assert_eq!(
+            rect().intersection(&rect().translate(vec2(100.0, 0.0))),
+            None
+        );
+    }
+
+    #[test]
+    fn rect_closest_point_and_distance() {
+        let rect = rect();
+
+        assert_eq!(rect.closest_point(vec2(0.0, 40.0)), vec2(10.0, 40.0));
+        assert_eq!(rect.closest_point(vec2(50.0, 70.0)), vec2(40.0, 60.0));
+        assert_eq!(rect.closest_point(vec2(20.0, 30.0)), vec2(20.0, 30.0));
+        assert_eq!(rect.distance(vec2(43.0, 64.0)), 5.0);
+        assert_eq!(rect.distance(vec2(20.0, 30.0)), 0.0);
+    }
+
+    #[test]
+    fn circle_closest_point_and_distance() {
+        let circle = Circle::new(vec2(10.0, 10.0), 5.0);
+
+        assert!(circle.contains(vec2(13.0, 14.0)));
+        assert!(!circle.contains(vec2(14.0, 14.0)));
+        assert_eq!(circle.closest_point(vec2(30.0, 10.0)), vec2(15.0, 10.0));
+        assert_eq!(circle.closest_point(vec2(12.0, 11.0)), vec2(12.0, 11.0));
+        assert_eq!(circle.distance(vec2(10.0, -5.0)), 10.0);
+        assert_eq!(circle.distance(vec2(10.0, 10.0)), 0.0);
+    }
+
+    #[test]
+    fn segment_closest_point_and_distance() {
+        let segment = Segment::new(vec2(0.0, 0.0), vec2(10.0, 0.0));
+
+        assert_eq!(segment.closest_point(vec2(4.0, 3.0)), vec2(4.0, 0.0));
+        assert_eq!(segment.closest_point(vec2(-5.0, 3.0)), vec2(0.0, 0.0));
+        assert_eq!(segment.closest_point(vec2(15.0, -3.0)), vec2(10.0, 0.0));
+        assert_eq!(segment.distance(vec2(13.0, 4.0)), 5.0);
+
+        let point = Segment::new(vec2(1.0, 1.0), vec2(1.0, 1.0));
+
+        assert_eq!(point.closest_point(vec2(4.0, 5.0)), vec2(1.0, 1.0));
+        assert_eq!(point.distance(vec2(4.0, 5.0)), 5.0);
+    }
+
+    #[test]
+    fn segment_intersection_of_crossing_segments() {
+        let a = Segment::new(vec2(0.0, 0.0), vec2(10.0, 10.0));
+        let b = Segment::new(vec2(0.0, 10.0), vec2(10.0, 0.0));
+
+        assert_eq!(a.intersection(&b), Some(vec2(5.0, 5.0)));
+        assert_eq!(b.intersection(&a), Some(vec2(5.0, 5.0)));
+
+        let touching = Segment::new(vec2(10.0, 10.0), vec2(20.0, 0.0));
+
+        assert_eq!(a.intersection(&touching), Some(vec2(10.0, 10.0)));
+    }
+
+    #[test]
+    fn segment_intersection_misses_short_segments() {
+        let a = Segment::new(vec2(0.0, 0.0), vec2(10.0, 10.0));
+        let short = Segment::new(vec2(0.0, 10.0), vec2(4.0, 6.0));
+
+        assert_eq!(a.intersection(&short), None);
+    }
+
+    #[test]
+    fn segment_intersection_of_parallel_segments() {
+        let a = Segment::new(vec2(0.0, 0.0), vec2(10.0, 0.0));
+        let b = Segment::new(vec2(0.0, 1.0), vec2(10.0, 1.0));
+
+        assert_eq!(a.intersection(&b), None);
+    }
+
+    #[test]
+    fn segment_intersection_of_collinear_segments() {
+        let a = Segment::new(vec2(0.0, 0.0), vec2(10.0, 0.0));
+        let overlapping = Segment::new(vec2(15.0, 0.0), vec2(5.0, 0.0));
+        let inside = Segment::new(vec2(2.0, 0.0), vec2(3.0, 0.0));
+        let apart = Segment::new(vec2(11.0, 0.0), vec2(20.0, 0.0));
+
+        assert_eq!(a.intersection(&overlapping), Some(vec2(5.0, 0.0)));
+        assert_eq!(overlapping.intersection(&a), Some(vec2(10.0, 0.0)));
+        assert_eq!(a.intersection(&inside), Some(vec2(2.0, 0.0)));
+        assert_eq!(inside.intersection(&a), Some(vec2(2.0, 0.0)));
+        assert_eq!(a.intersection(&apart), None);
+    }
+
+    #[test]
+    fn ray_intersects_rect() {
+        let rect = rect();
+        let ray = Ray::new(vec2(0.0, 40.0), vec2(1.0, 0.0));
+
+        assert_eq!(ray.intersect_aabb(&rect), Some(math::real(10.0)));
+        assert_eq!(ray.at(math::real(10.0)), vec2(10.0, 40.0));
+        assert_eq!(
+            Ray::new(rect.center(), vec2(0.0, 1.0)).intersect_aabb(&rect),
+            Some(math::real(0.0))
+        );
+    }
+
+    #[test]
+    fn ray_misses_rect() {
+        let rect = rect();
+
+        assert_eq!(
+            Ray::new(vec2(0.0, 40.0), vec2(-1.0, 0.0)).intersect_aabb(&rect),
+            None
+        );
+        assert_eq!(
+            Ray::new(vec2(0.0, 0.0), vec2(1.0, 0.0)).intersect_aabb(&rect),
+            None
+        );
+        assert_eq!(
+            Ray::new(vec2(0.0, 0.0), vec2(1.0, 10.0)).intersect_aabb(&rect),
+            None
+        );
+    }
+
+    #[test]
+    fn ray_intersects_circle() {
+        let circle = Circle::new(vec2(20.0, 0.0), 5.0);
+
+        assert_eq!(
+            Ray::new(Vector::ZERO, vec2(1.0, 0.0)).intersect_circle(&circle),
+            Some(math::real(15.0))
+        );
+        assert_eq!(
+            Ray::new(vec2(20.0, 1.0), vec2(0.0, 1.0)).intersect_circle(&circle),
+            Some(math::real(0.0))
+        );
+    }
+
+    #[test]
+    fn ray_misses_circle() {
+        let circle = Circle::new(vec2(20.0, 0.0), 5.0);
+
+        assert_eq!(
+            Ray::new(Vector::ZERO, vec2(-1.0, 0.0)).intersect_circle(&circle),
+            None
+        );
+        assert_eq!(
+            Ray::new(vec2(0.0, 6.0), vec2(1.0, 0.0)).intersect_circle(&circle),
+            None
+        );
+    }
+
+    #[test]
+    fn ray_intersects_segment() {
+        let segment = Segment::new(vec2(10.0, -5.0), vec2(10.0, 5.0));
+
+        assert_eq!(
+            Ray::new(Vector::ZERO, vec2(1.0, 0.0)).intersect_segment(&segment),
+            Some(math::real(10.0))
+        );
+    }
+
+    #[test]
+    fn ray_misses_segment() {
+        let segment = Segment::new(vec2(10.0, -5.0), vec2(10.0, 5.0));
+
+        assert_eq!(
+            Ray::new(Vector::ZERO, vec2(-1.0, 0.0)).intersect_segment(&segment),
+            None
+        );
+        assert_eq!(
+            Ray::new(vec2(0.0, 6.0), vec2(1.0, 0.0)).intersect_segment(&segment),
+            None
+        );
+        assert_eq!(
+            Ray::new(Vector::ZERO, vec2(0.0, 1.0)).intersect_segment(&segment),
+            None
+        );
+    }
+
+    fn square() -> Polygon {
+        Polygon::new(&[
+            vec2(0.0, 0.0),
+            vec2(10.0, 0.0),
+            vec2(10.0, 10.0),
+            vec2(0.0, 10.0),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn polygon_contains() {
+        let triangle = Polygon::new(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 10.0)]).unwrap();
+
+        assert!(square().contains(vec2(5.0, 5.0)));
+        assert!(square().contains(vec2(10.0, 5.0)));
+        assert!(!square().contains(vec2(11.0, 5.0)));
+        assert!(triangle.contains(vec2(2.0, 2.0)));
+        assert!(!triangle.contains(vec2(6.0, 6.0)));
+    }
+
+    #[test]
+    fn polygon_closest_point_and_distance() {
+        let triangle = Polygon::new(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 10.0)]).unwrap();
+
+        assert_eq!(square().closest_point(vec2(5.0, 5.0)), vec2(5.0, 5.0));
+        assert_eq!(square().closest_point(vec2(15.0, 5.0)), vec2(10.0, 5.0));
+        assert_eq!(square().closest_point(vec2(13.0, 14.0)), vec2(10.0, 10.0));
+        assert_eq!(square().distance(vec2(13.0, 14.0)), 5.0);
+        assert_eq!(triangle.closest_point(vec2(10.0, 10.0)), vec2(5.0, 5.0));
+    }
+
+    #[test]
+    fn polygon_matches_its_rect() {
+        let rect = rect();
+        let polygon = Polygon::from(rect);
+
+        assert_eq!(polygon.bounds(), rect);
+
+        for point in [vec2(0.0, 0.0), vec2(25.0, 40.0), vec2(50.0, 30.0)] {
+            assert_eq!(polygon.contains(point), rect.contains(point));
+            assert_eq!(polygon.closest_point(point), rect.closest_point(point));
+        }
+    }
+
+    #[test]
+    fn obb_contains_and_bounds() {
+        let obb = Obb::new(vec2(10.0, 10.0), vec2(5.0, 1.0), TAU / 8.0);
+
+        assert!(obb.contains(vec2(12.0, 12.0)));
+        assert!(!obb.contains(vec2(14.0, 10.0)));
+
+        let extent = 6.0 / 2f32.sqrt();
+
+        assert_near(obb.bounds().min, vec2(10.0 - extent, 10.0 - extent));
+        assert_near(obb.bounds().max, vec2(10.0 + extent, 10.0 + extent));
+    }
+
+    #[test]
+    fn shape_closest_point_and_distance() {
+        let shape = Shape::from(Obb::new(vec2(0.0, 0.0), vec2(5.0, 5.0), TAU / 4.0));
+
+        assert_near(shape.closest_point(vec2(10.0, 0.0)), vec2(5.0, 0.0));
+        assert!((shape.distance(vec2(0.0, -8.0)) - 3.0).abs() < EPSILON);
+        assert_eq!(shape.distance(Vector::ZERO), 0.0);
+    }
+
+    #[test]
+    fn transform_round_trips() {
+        let transform = Transform2D::new(vec2(3.0, -4.0), TAU / 4.0, vec2(2.0, 0.5));
+        let point = vec2(1.0, 2.0);
+
+        assert_near(transform.transform_point(point), vec2(2.0, -2.0));
+        assert_near(
+            transform.inverse_transform_point(transform.transform_point(point)),
+            point,
+        );
+        assert_near(transform.transform_vector(point), vec2(-1.0, 2.0));
+        assert_near(Transform2D::IDENTITY.transform_point(point), point);
+        assert!(Transform2D::from_translation(point).is_translation());
+        assert!(!Transform2D::from_rotation(1.0).is_translation());
+    }
+
+    #[test]
+    fn polygon_accepts_convex_vertices_in_either_direction() {
+        let triangle = [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 10.0)];
+        let mut reversed = triangle;
+        reversed.reverse();
+
+        assert!(Polygon::new(&triangle).is_some());
+        assert!(Polygon::new(&reversed).is_some());
+    }
+
+    #[test]
+    fn polygon_rejects_wrong_vertex_counts() {
+        assert!(Polygon::new(&[vec2(0.0, 0.0), vec2(1.0, 0.0)]).is_none());
+
+        let circle: Vec<Vector> = (0..Polygon::MAX_VERTICES + 1)
+            .map(|i| {
+                Vector::from_angle(math::real(
+                    TAU * i as f32 / (Polygon::MAX_VERTICES + 1) as f32,
+                ))
+            })
+            .collect();
+
+        assert!(Polygon::new(&circle).is_none());
+        assert!(Polygon::new(&circle[..Polygon::MAX_VERTICES]).is_some());
+    }
+
+    #[test]
+    fn polygon_rejects_concave_and_degenerate_vertices() {
+        let arrow = [
+            vec2(0.0, 0.0),
+            vec2(10.0, 5.0),
+            vec2(0.0, 10.0),
+            vec2(3.0, 5.0),
+        ];
+        let aligned = [vec2(0.0, 0.0), vec2(5.0, 0.0), vec2(10.0, 0.0)];
+        let repeated = [
+            vec2(0.0, 0.0),
+            vec2(0.0, 0.0),
+            vec2(10.0, 0.0),
+            vec2(0.0, 10.0),
+        ];
+
+        assert!(Polygon::new(&arrow).is_none());
+        assert!(Polygon::new(&aligned).is_none());
+        assert!(Polygon::new(&repeated).is_none());
+    }
+
+    #[test]
+    fn polygon_rejects_stars() {
+        let star: Vec<Vector> = (0..5)
+            .map(|i| Vector::from_angle(math::real(TAU * (i * 2) as f32 / 5.0)))
+            .collect();
+
+        assert!(Polygon::new(&star).is_none());
+    }
 }
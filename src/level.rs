@@ -0,0 +1,734 @@
+//! Plain text levels, so they can be edited without recompiling.
+//!
+//! ```text
+//! # Lines starting with '#' are comments.
+//! [level]
+//! name = Classic
+//! ball_speed = 300
+//!
+//! [bricks]
+//! # symbol = hit points, colors, behaviors...
+//! B = 1, #d2b48c
+//! # One color per hit point lost, from full life down.
+//! M = 3, #b22222 #cd5c5c #f08080
+//! X = unbreakable, #808080
+//! # Deals 1 damage, or the given amount, to the bricks around it when broken.
+//! E = 1, #ff8c00, explosive 2
+//! # Gets a hit point back after 8 seconds without being hit.
+//! R = 2, #3cb371 #8fbc8f, regenerates 8
+//! # Drops a power-up half of the times it breaks.
+//! P = 1, #9370db, drops 0.5
+//! # Goes 200 pixels right and back every 4 seconds. Bricks can also move
+//! # with `circular radius seconds`, around their place, or `spline seconds
+//! # x y x y...`, through their place and the offsets given.
+//! S = 1, #4682b4, moves linear 200 0 4
+//!
+//! [layout]
+//! # One symbol per brick, '.' or a space for a gap.
+//! MMMMMMM
+//! BXE.PRB
+//! S......
+//! ```
+//!
+//! A [`Campaign`] lists level files in the order they are played, one per
+//! line, and the player's [`Progress`] through it is saved as `key = value`
+//! lines like the `[level]` section.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use ggez::glam::vec2;
+use ggez::glam::Vec2;
+use ggez::graphics::Color;
+use ggez::GameError;
+
+use crate::physics::path::Path;
+
+const DEFAULT_BALL_SPEED: f32 = 300.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelError {
+    /// Line the error was found on, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl LevelError {
+    fn new(line: usize, message: impl Into<String>) -> LevelError {
+        LevelError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<LevelError> for GameError {
+    fn from(value: LevelError) -> Self {
+        GameError::ResourceLoadError(format!("Invalid level, {value}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrickType {
+    /// Hit points, `None` for bricks that can't be broken.
+    pub life: Option<u8>,
+    /// Color at full life, then one for each hit point lost. The last one is
+    /// kept once they run out.
+    pub colors: Vec<Color>,
+    /// Damage dealt to the bricks around it when broken.
+    pub explosive: Option<u8>,
+    /// Seconds without being hit before getting a hit point back.
+    pub regenerates: Option<f32>,
+    /// Chance from 0 to 1 of dropping a power-up when broken.
+    pub drop_chance: f32,
+    /// Path followed around its place in the layout, in pixels.
+    pub path: Option<Path>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Level {
+    pub name: String,
+    pub ball_speed: f32,
+    pub bricks: HashMap<char, BrickType>,
+    /// Rows from top to bottom, `None` for gaps.
+    pub layout: Vec<Vec<Option<char>>>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Section {
+    Level,
+    Bricks,
+    Layout,
+}
+
+impl Level {
+    pub fn parse(source: &str) -> Result<Level, LevelError> {
+        let mut level = Level {
+            name: String::new(),
+            ball_speed: DEFAULT_BALL_SPEED,
+            bricks: HashMap::new(),
+            layout: Vec::new(),
+        };
+
+        let mut section = None;
+        // Layout rows are checked once every brick type is known.
+        let mut rows = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let number = index + 1;
+            let trimmed = line.trim();
+
+            if trimmed.starts_with('#') || (trimmed.is_empty() && section != Some(Section::Layout))
+            {
+                continue;
+            }
+
+            if let Some(name) = trimmed
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                section = Some(match name {
+                    "level" => Section::Level,
+                    "bricks" => Section::Bricks,
+                    "layout" => Section::Layout,
+                    _ => return Err(LevelError::new(number, format!("unknown section [{name}]"))),
+                });
+
+                continue;
+            }
+
+            match section {
+                Some(Section::Level) => {
+                    let (key, value) = split_pair(number, trimmed)?;
+
+                    match key {
+                        "name" => level.name = value.to_string(),
+                        "ball_speed" => level.ball_speed = parse_positive(number, value)?,
+                        _ => return Err(LevelError::new(number, format!("unknown key {key}"))),
+                    }
+                }
+                Some(Section::Bricks) => {
+                    let (symbol, brick) = parse_brick(number, trimmed)?;
+
+                    if level.bricks.insert(symbol, brick).is_some() {
+                        return Err(LevelError::new(
+                            number,
+                            format!("brick '{symbol}' is already defined"),
+                        ));
+                    }
+                }
+                Some(Section::Layout) => rows.push((number, line.trim_end())),
+                None => return Err(LevelError::new(number, "expected a [section] first")),
+            }
+        }
+
+        // Blank lines after the last row are not part of the layout.
+        while rows.last().is_some_and(|(_, row)| row.is_empty()) {
+            rows.pop();
+        }
+
+        for (number, row) in rows {
+            let row = row
+                .chars()
+                .map(|symbol| match symbol {
+                    '.' | ' ' => Ok(None),
+                    _ if level.bricks.contains_key(&symbol) => Ok(Some(symbol)),
+                    _ => Err(LevelError::new(number, format!("unknown brick '{symbol}'"))),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            level.layout.push(row);
+        }
+
+        Ok(level)
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.layout.len()
+    }
+
+    /// Width of the widest row.
+    pub fn columns(&self) -> usize {
+        self.layout.iter().map(|row| row.len()).max().unwrap_or(0)
+    }
+
+    /// Every brick as its column, row and symbol in [`Level::bricks`].
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+        self.layout.iter().enumerate().flat_map(|(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .filter_map(move |(column, symbol)| symbol.map(|symbol| (column, row, symbol)))
+        })
+    }
+}
+
+/// Ordered level files making up the game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Campaign {
+    pub levels: Vec<String>,
+}
+
+impl Campaign {
+    pub fn parse(source: &str) -> Result<Campaign, LevelError> {
+        let levels: Vec<String> = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+
+        if levels.is_empty() {
+            return Err(LevelError::new(1, "a campaign needs at least one level"));
+        }
+
+        Ok(Campaign { levels })
+    }
+
+    #[inline]
+    pub fn is_last(&self, level: usize) -> bool {
+        level + 1 >= self.levels.len()
+    }
+}
+
+/// How far the player got, kept between levels and sessions.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Progress {
+    /// Index of the level in the [`Campaign`].
+    pub level: usize,
+    pub score: u32,
+    pub lives: u8,
+}
+
+impl Progress {
+    /// Start of the campaign.
+    pub fn new(lives: u8) -> Progress {
+        Progress {
+            level: 0,
+            score: 0,
+            lives,
+        }
+    }
+
+    pub fn parse(source: &str) -> Result<Progress, LevelError> {
+        let mut progress = Progress::new(0);
+
+        for (index, line) in source.lines().enumerate() {
+            let number = index + 1;
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = split_pair(number, trimmed)?;
+
+            match key {
+                "level" => progress.level = parse_number(number, value)?,
+                "score" => progress.score = parse_number(number, value)?,
+                "lives" => progress.lives = parse_number(number, value)?,
+                _ => return Err(LevelError::new(number, format!("unknown key {key}"))),
+            }
+        }
+
+        Ok(progress)
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "level = {}", self.level)?;
+        writeln!(f, "score = {}", self.score)?;
+        writeln!(f, "lives = {}", self.lives)
+    }
+}
+
+fn split_pair(line: usize, text: &str) -> Result<(&str, &str), LevelError> {
+    match text.split_once('=') {
+        Some((key, value)) => Ok((key.trim(), value.trim())),
+        None => Err(LevelError::new(line, "expected key = value")),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(line: usize, text: &str) -> Result<T, LevelError> {
+    text.parse()
+        .map_err(|_| LevelError::new(line, format!("invalid number {text}")))
+}
+
+fn parse_brick(line: usize, text: &str) -> Result<(char, BrickType), LevelError> {
+    let (symbol, value) = split_pair(line, text)?;
+
+    let mut chars = symbol.chars();
+
+    let symbol = match (chars.next(), chars.next()) {
+        (Some(symbol), None) if symbol != '.' && symbol != '#' => symbol,
+        _ => {
+            return Err(LevelError::new(
+                line,
+                format!(
+                    "brick symbols are a single character other than '.' and '#', got {symbol}"
+                ),
+            ))
+        }
+    };
+
+    let mut fields = value.split(',').map(str::trim);
+
+    let life = match fields.next().unwrap_or_default() {
+        "unbreakable" => None,
+        life => match parse_number(line, life)? {
+            0 => return Err(LevelError::new(line, "bricks need at least 1 hit point")),
+            life => Some(life),
+        },
+    };
+
+    let colors = fields
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|color| parse_color(line, color))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if colors.is_empty() {
+        return Err(LevelError::new(line, "expected hit points, colors"));
+    }
+
+    let mut brick = BrickType {
+        life,
+        colors,
+        explosive: None,
+        regenerates: None,
+        drop_chance: 0.0,
+        path: None,
+    };
+
+    for behavior in fields {
+        let words: Vec<&str> = behavior.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["explosive"] => brick.explosive = Some(1),
+            ["explosive", damage] => brick.explosive = Some(parse_number(line, damage)?),
+            ["regenerates", seconds] => brick.regenerates = Some(parse_positive(line, seconds)?),
+            ["drops", chance] => {
+                brick.drop_chance = parse_number(line, chance)?;
+
+                if !(0.0..=1.0).contains(&brick.drop_chance) {
+                    return Err(LevelError::new(
+                        line,
+                        format!("drop chance goes from 0 to 1, got {chance}"),
+                    ));
+                }
+            }
+            ["moves", path @ ..] => brick.path = Some(parse_path(line, path)?),
+            _ => {
+                return Err(LevelError::new(
+                    line,
+                    format!("unknown brick behavior {behavior}"),
+                ))
+            }
+        }
+    }
+
+    Ok((symbol, brick))
+}
+
+/// Parses the words after `moves`, relative to the place of the brick.
+fn parse_path(line: usize, words: &[&str]) -> Result<Path, LevelError> {
+    match words {
+        ["linear", x, y, period] => Ok(Path::Linear {
+            from: Vec2::ZERO,
+            to: vec2(parse_finite(line, x)?, parse_finite(line, y)?),
+            period: parse_positive(line, period)?,
+        }),
+        ["circular", radius, period] => {
+            // Negative periods turn the other way.
+            let period = parse_finite(line, period)?;
+
+            if period == 0.0 {
+                return Err(LevelError::new(
+                    line,
+                    "circular paths need a period other than 0",
+                ));
+            }
+
+            Ok(Path::Circular {
+                center: Vec2::ZERO,
+                radius: parse_positive(line, radius)?,
+                period,
+            })
+        }
+        ["spline", duration, offsets @ ..] if offsets.len() % 2 == 0 => {
+            let offsets = offsets
+                .iter()
+                .map(|offset| parse_finite(line, offset))
+                .collect::<Result<Vec<f32>, _>>()?;
+
+            let points = std::iter::once(Vec2::ZERO)
+                .chain(offsets.chunks(2).map(|offset| vec2(offset[0], offset[1])))
+                .collect();
+
+            Path::spline(points, parse_positive(line, duration)?)
+                .ok_or_else(|| LevelError::new(line, "spline paths need at least one offset"))
+        }
+        _ => Err(LevelError::new(
+            line,
+            format!(
+                "unknown path {}, expected linear x y seconds, circular radius seconds \
+                 or spline seconds x y...",
+                words.join(" ")
+            ),
+        )),
+    }
+}
+
+fn parse_finite(line: usize, text: &str) -> Result<f32, LevelError> {
+    match parse_number::<f32>(line, text)? {
+        value if value.is_finite() => Ok(value),
+        _ => Err(LevelError::new(line, format!("invalid number {text}"))),
+    }
+}
+
+/// Parses a number above 0, for speeds and durations.
+fn parse_positive(line: usize, text: &str) -> Result<f32, LevelError> {
+    match parse_finite(line, text)? {
+        value if value > 0.0 => Ok(value),
+        _ => Err(LevelError::new(
+            line,
+            format!("expected a number above 0, got {text}"),
+        )),
+    }
+}
+
+/// Parses `#rrggbb`.
+fn parse_color(line: usize, text: &str) -> Result<Color, LevelError> {
+    let invalid = || LevelError::new(line, format!("invalid color {text}, expected #rrggbb"));
+
+    let hex = text.strip_prefix('#').ok_or_else(invalid)?;
+
+    if hex.len() != 6 {
+        return Err(invalid());
+    }
+
+    let channel =
+        |range| u8::from_str_radix(hex.get(range).ok_or_else(invalid)?, 16).map_err(|_| invalid());
+
+    Ok(Color::from_rgb(
+        channel(0..2)?,
+        channel(2..4)?,
+        channel(4..6)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example of the module documentation.
+    const EXAMPLE: &str = "\
+# Lines starting with '#' are comments.
+[level]
+name = Classic
+ball_speed = 300
+
+[bricks]
+# symbol = hit points, colors, behaviors...
+B = 1, #d2b48c
+# One color per hit point lost, from full life down.
+M = 3, #b22222 #cd5c5c #f08080
+X = unbreakable, #808080
+# Deals 1 damage, or the given amount, to the bricks around it when broken.
+E = 1, #ff8c00, explosive 2
+# Gets a hit point back after 8 seconds without being hit.
+R = 2, #3cb371 #8fbc8f, regenerates 8
+# Drops a power-up half of the times it breaks.
+P = 1, #9370db, drops 0.5
+# Goes 200 pixels right and back every 4 seconds. Bricks can also move
+# with `circular radius seconds`, around their place, or `spline seconds
+# x y x y...`, through their place and the offsets given.
+S = 1, #4682b4, moves linear 200 0 4
+
+[layout]
+# One symbol per brick, '.' or a space for a gap.
+MMMMMMM
+BXE.PRB
+S......
+";
+
+    fn brick(definition: &str) -> Result<BrickType, LevelError> {
+        parse_brick(1, definition).map(|(_, brick)| brick)
+    }
+
+    /// The error `source` fails with.
+    fn error(source: &str) -> LevelError {
+        match Level::parse(source) {
+            Ok(level) => panic!("Expected an error, parsed {level:?}"),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn parses_the_example() {
+        let level = Level::parse(EXAMPLE).unwrap();
+
+        assert_eq!(level.name, "Classic");
+        assert_eq!(level.ball_speed, 300.0);
+        assert_eq!(level.bricks.len(), 7);
+        assert_eq!((level.rows(), level.columns()), (3, 7));
+        assert_eq!(level.cells().count(), 14);
+        assert_eq!(level.layout[1][3], None);
+        assert_eq!(level.cells().nth(7), Some((0, 1, 'B')));
+
+        assert_eq!(
+            level.bricks[&'M'],
+            BrickType {
+                life: Some(3),
+                colors: vec![
+                    Color::from_rgb(0xb2, 0x22, 0x22),
+                    Color::from_rgb(0xcd, 0x5c, 0x5c),
+                    Color::from_rgb(0xf0, 0x80, 0x80),
+                ],
+                explosive: None,
+                regenerates: None,
+                drop_chance: 0.0,
+                path: None,
+            }
+        );
+        assert_eq!(level.bricks[&'X'].life, None);
+        assert_eq!(level.bricks[&'E'].explosive, Some(2));
+        assert_eq!(level.bricks[&'R'].regenerates, Some(8.0));
+        assert_eq!(level.bricks[&'P'].drop_chance, 0.5);
+        assert!(level.bricks[&'S'].path.is_some());
+    }
+
+    #[test]
+    fn defaults_without_a_level_section() {
+        let level = Level::parse("[bricks]\nB = 1, #ffffff\n[layout]\nB.B\n\n").unwrap();
+
+        assert_eq!(level.name, "");
+        assert_eq!(level.ball_speed, DEFAULT_BALL_SPEED);
+        assert_eq!(level.layout, vec![vec![Some('B'), None, Some('B')]]);
+    }
+
+    #[test]
+    fn rejects_invalid_ball_speeds() {
+        for speed in ["0", "-300", "NaN", "inf", "fast", ""] {
+            let error = error(&format!("[level]\nball_speed = {speed}\n"));
+
+            assert_eq!(error.line, 2, "ball_speed = {speed}");
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_bricks() {
+        let error = error("[bricks]\nB = 1, #ffffff\n\nB = 2, #000000 #ffffff\n");
+
+        assert_eq!(error, LevelError::new(4, "brick 'B' is already defined"));
+    }
+
+    #[test]
+    fn rejects_invalid_structure() {
+        let cases = [
+            ("name = Classic\n", 1, "expected a [section] first"),
+            ("[levels]\n", 1, "unknown section [levels]"),
+            ("[level]\nname Classic\n", 2, "expected key = value"),
+            ("[level]\nspeed = 300\n", 2, "unknown key speed"),
+            (
+                "[bricks]\nB = 1, #ffffff\n[layout]\nBB\nBC\n",
+                5,
+                "unknown brick 'C'",
+            ),
+        ];
+
+        for (source, line, message) in cases {
+            assert_eq!(error(source), LevelError::new(line, message), "{source}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_bricks() {
+        for definition in [
+            "BB = 1, #ffffff",
+            ". = 1, #ffffff",
+            "= 1, #ffffff",
+            "B 1, #ffffff",
+            "B = 0, #ffffff",
+            "B = 256, #ffffff",
+            "B = strong, #ffffff",
+            "B = 1",
+            "B = 1,",
+            "B = 1, ffffff",
+            "B = 1, #fffff",
+            "B = 1, #gggggg",
+            "B = 1, #ffffff, explosive big",
+            "B = 1, #ffffff, explosive 1 2",
+            "B = 1, #ffffff, regenerates",
+            "B = 1, #ffffff, regenerates 0",
+            "B = 1, #ffffff, regenerates -8",
+            "B = 1, #ffffff, regenerates NaN",
+            "B = 1, #ffffff, regenerates inf",
+            "B = 1, #ffffff, drops 1.5",
+            "B = 1, #ffffff, drops -0.5",
+            "B = 1, #ffffff, drops NaN",
+            "B = 1, #ffffff, bounces",
+        ] {
+            assert!(brick(definition).is_err(), "{definition}");
+
+            // Errors point at the line of the definition.
+            let error = error(&format!("[bricks]\n{definition}\n"));
+
+            assert_eq!(error.line, 2, "{definition}");
+        }
+    }
+
+    #[test]
+    fn parses_the_bundled_levels() {
+        let campaign = Campaign::parse(include_str!("../resources/levels/campaign")).unwrap();
+        let levels = [
+            include_str!("../resources/levels/01.level"),
+            include_str!("../resources/levels/02.level"),
+            include_str!("../resources/levels/03.level"),
+        ];
+
+        assert_eq!(campaign.levels.len(), levels.len());
+
+        for source in levels {
+            let level = Level::parse(source).unwrap();
+
+            assert!(level.cells().count() > 0, "{}", level.name);
+        }
+    }
+
+    #[test]
+    fn parses_campaigns() {
+        let campaign = Campaign::parse("# First\n01.level\n\n 02.level \n").unwrap();
+
+        assert_eq!(campaign.levels, vec!["01.level", "02.level"]);
+        assert!(!campaign.is_last(0));
+        assert!(campaign.is_last(1));
+        assert!(Campaign::parse("# Nothing\n\n").is_err());
+    }
+
+    #[test]
+    fn progress_round_trips() {
+        let progress = Progress {
+            level: 2,
+            score: 1230,
+            lives: 1,
+        };
+
+        assert_eq!(Progress::parse(&progress.to_string()), Ok(progress));
+        assert_eq!(Progress::parse(""), Ok(Progress::new(0)));
+        assert!(Progress::parse("level = -1").is_err());
+        assert!(Progress::parse("levels = 1").is_err());
+    }
+
+    #[test]
+    fn parses_paths() {
+        assert_eq!(
+            brick("S = 1, #ffffff, moves linear 200 -10 4")
+                .unwrap()
+                .path,
+            Some(Path::Linear {
+                from: Vec2::ZERO,
+                to: vec2(200.0, -10.0),
+                period: 4.0,
+            })
+        );
+        assert_eq!(
+            brick("S = 1, #ffffff, moves circular 30 -2").unwrap().path,
+            Some(Path::Circular {
+                center: Vec2::ZERO,
+                radius: 30.0,
+                period: -2.0,
+            })
+        );
+        assert_eq!(
+            brick("S = 1, #ffffff, moves spline 1.5 100 0 100 50")
+                .unwrap()
+                .path,
+            Some(Path::Spline {
+                points: vec![Vec2::ZERO, vec2(100.0, 0.0), vec2(100.0, 50.0)],
+                segment_duration: 1.5,
+            })
+        );
+        assert_eq!(brick("B = 1, #ffffff").unwrap().path, None);
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        for definition in [
+            "S = 1, #ffffff, moves",
+            "S = 1, #ffffff, moves linear 200 0",
+            "S = 1, #ffffff, moves linear 200 0 0",
+            "S = 1, #ffffff, moves linear 200 0 -4",
+            "S = 1, #ffffff, moves linear inf 0 4",
+            "S = 1, #ffffff, moves circular 30 0",
+            "S = 1, #ffffff, moves circular -30 2",
+            "S = 1, #ffffff, moves spline 1",
+            "S = 1, #ffffff, moves spline 1 100",
+            "S = 1, #ffffff, moves spline NaN 100 0",
+            "S = 1, #ffffff, moves zigzag 1",
+        ] {
+            assert!(brick(definition).is_err(), "{definition}");
+        }
+    }
+
+    #[test]
+    fn rejects_splines_without_offsets() {
+        let error = error("[bricks]\nB = 1, #ffffff\nS = 1, #ffffff, moves spline 1\n");
+
+        assert_eq!(
+            error,
+            LevelError::new(3, "spline paths need at least one offset")
+        );
+    }
+}
@@ -1,3 +1,9 @@
+// Lets the derive macros, which refer to `breakout::...`, be used inside the crate.
+extern crate self as breakout;
+
+pub mod app;
 pub mod ecs;
 pub mod geometry;
+pub mod level;
+pub mod math;
 pub mod physics;
@@ -1,103 +1,380 @@
-use std::borrow::BorrowMut;
-
+use std::cell::Ref;
+use std::collections::HashMap;
+use std::env;
+use std::io::Read;
+use std::io::Write;
+use std::path::PathBuf;
+
+use breakout::app::App;
+use breakout::app::ClearColor;
+use breakout::app::FixedTime;
+use breakout::app::Plugin;
+use breakout::app::Stage;
 use breakout::ecs::entity::Entity;
+use breakout::ecs::event::Events;
+use breakout::ecs::world::query::QueryState;
 use breakout::ecs::world::World;
-use breakout::geometry;
-use breakout::physics::collision;
+use breakout::geometry::Aabb;
+use breakout::geometry::Circle;
+use breakout::level::Campaign;
+use breakout::level::Level;
+use breakout::level::Progress;
+use breakout::math;
+use breakout::math::Real;
+use breakout::math::Scalar;
+use breakout::physics::body::Collider;
+use breakout::physics::body::Position;
+use breakout::physics::body::Restitution;
+use breakout::physics::body::RigidBody;
+use breakout::physics::body::Velocity;
+use breakout::physics::broadphase::SpatialGrid;
+use breakout::physics::path::MotionPath;
+use breakout::physics::step::CollisionStarted;
+use breakout::physics::step::PhysicsPlugin;
 use breakout_macros::Component;
 use ggez::conf::Conf;
-use ggez::event;
-use ggez::event::EventHandler;
 use ggez::glam::vec2;
 use ggez::glam::Vec2;
-use ggez::graphics;
 use ggez::graphics::Canvas;
 use ggez::graphics::Color;
 use ggez::graphics::DrawMode;
 use ggez::graphics::Mesh;
 use ggez::graphics::Rect;
+use ggez::graphics::Text;
+use ggez::graphics::TextLayout;
 use ggez::input::keyboard::KeyCode;
 use ggez::Context;
 use ggez::ContextBuilder;
 use ggez::GameError;
+use rand::rngs::StdRng;
 use rand::Rng;
+use rand::SeedableRng;
+
+use self::power_up::PowerUp;
+
+mod power_up;
 
 const PLAYER_WIDTH: f32 = 170f32;
 const PLAYER_HEIGHT: f32 = 30f32;
+const PLAYER_SPEED: f32 = 300f32;
 
 const BLOCK_WIDTH: f32 = 100.0;
 const BLOCK_HEIGHT: f32 = 40.0;
-const BLOCK_LIFE: u8 = 5;
+const BLOCK_PADDING: f32 = 5.0;
 
-const BALL_SPEED: f32 = 300f32;
 const BALL_RADIUS: f32 = 10f32;
 
+const WALL_THICKNESS: f32 = 100.0;
+
+const LEVELS_DIR: &str = "/levels";
+const CAMPAIGN_FILE: &str = "/levels/campaign";
+/// Saved in the user data directory.
+const PROGRESS_FILE: &str = "/progress";
+
+const STARTING_LIVES: u8 = 3;
+const MAX_LIVES: u8 = 9;
+const BRICK_SCORE: u32 = 10;
+/// Seconds the level cleared screen is shown for.
+const TRANSITION_SECS: f32 = 2.0;
+
+/// Seed of every random choice with the `deterministic` feature, so runs replay.
+const RNG_SEED: u64 = 0x0b5e_55ed;
+
+const LAYER_PADDLE: u32 = 1 << 0;
+const LAYER_BALL: u32 = 1 << 1;
+const LAYER_BRICK: u32 = 1 << 2;
+const LAYER_WALL: u32 = 1 << 3;
+const LAYER_POWER_UP: u32 = 1 << 4;
+const LAYER_LASER: u32 = 1 << 5;
+
 fn main() -> Result<(), GameError> {
     let cfg = Conf::new();
-    let (mut ctx, event_loop) = ContextBuilder::new("breakout", "Joao Koritar")
-        .default_conf(cfg)
-        .build()
-        .expect("Could not create game context");
+    let mut context = ContextBuilder::new("breakout", "Joao Koritar").default_conf(cfg);
 
-    let state = GameState::new(&mut ctx).expect("Could not create game state");
+    // Load levels straight from the source tree while developing.
+    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        context = context.add_resource_path(PathBuf::from(manifest_dir).join("resources"));
+    }
 
-    event::run(ctx, event_loop, state)
+    App::new(context).add_plugin(BreakoutPlugin).run()
+}
+
+struct BreakoutPlugin;
+
+impl Plugin for BreakoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(Color::from([0.1, 0.2, 0.3, 1.0])))
+            .insert_resource(FixedTime::from_hz(120.0))
+            .insert_resource(PaddleBounce::default())
+            .insert_resource(BallCollisions(false))
+            .insert_resource(GameRng::default())
+            .insert_resource(Events::<Damage>::new())
+            .insert_resource(Events::<BrickBroken>::new())
+            .add_system(Stage::Startup, setup)
+            .add_system(Stage::Startup, power_up::setup)
+            .add_system(Stage::Update, update_transition)
+            .add_system(Stage::FixedUpdate, store_previous_positions)
+            .add_system(Stage::FixedUpdate, update_player)
+            .add_system(Stage::FixedUpdate, power_up::update_effects)
+            .add_system(Stage::FixedUpdate, power_up::resize_paddle)
+            .add_system(Stage::FixedUpdate, power_up::release_balls)
+            .add_system(Stage::FixedUpdate, power_up::apply_ball_effects)
+            .add_system(Stage::FixedUpdate, power_up::fire_lasers)
+            .add_plugin(PhysicsPlugin)
+            .add_system(Stage::FixedUpdate, bounce_off_paddle)
+            .add_system(Stage::FixedUpdate, power_up::catch_balls)
+            .add_system(Stage::FixedUpdate, power_up::pin_balls)
+            .add_system(Stage::FixedUpdate, |world, _| check_block_collisions(world))
+            .add_system(Stage::FixedUpdate, power_up::burn_bricks)
+            .add_system(Stage::FixedUpdate, |world, _| {
+                power_up::check_laser_hits(world)
+            })
+            .add_system(Stage::FixedUpdate, power_up::catch_power_ups)
+            .add_system(Stage::FixedUpdate, update_balls)
+            .add_system(Stage::FixedUpdate, |world, _| damage_bricks(world))
+            .add_system(Stage::FixedUpdate, update_blocks)
+            .add_system(Stage::FixedUpdate, explode_bricks)
+            .add_system(Stage::FixedUpdate, drop_power_ups)
+            .add_system(Stage::FixedUpdate, despawn_broken_bricks)
+            .add_system(Stage::FixedUpdate, regenerate_bricks)
+            .add_system(Stage::FixedUpdate, update_brick_shapes)
+            .add_system(Stage::FixedUpdate, check_level_cleared)
+            .add_system(Stage::Draw, draw_entities)
+            .add_system(Stage::Draw, draw_hud)
+            .add_system(Stage::Draw, power_up::draw_power_ups);
+    }
 }
 
 struct GameState {
-    world: World,
     player: Entity,
     blocks: Vec<Entity>,
     balls: Vec<Entity>,
+    power_ups: Vec<Entity>,
+    lasers: Vec<Entity>,
+    queries: Queries,
+}
+
+struct Queries {
+    drawables: QueryState<(&'static Position, &'static Shape, &'static PreviousPosition)>,
+    previous: QueryState<(&'static Position, &'static mut PreviousPosition)>,
+    player: QueryState<(&'static Position, &'static mut Velocity, &'static Paddle)>,
+    paddles: QueryState<&'static Paddle>,
+    positions: QueryState<&'static Position>,
+    velocities: QueryState<&'static mut Velocity>,
+    lives: QueryState<&'static Life>,
+    damage: QueryState<(&'static mut Life, &'static mut Regenerating)>,
+    bounds: QueryState<(&'static Position, &'static Collider)>,
+    explosive: QueryState<&'static Explosive>,
+    drops: QueryState<&'static PowerUpDrop>,
+    regenerating: QueryState<(
+        &'static mut Life,
+        &'static MaxLife,
+        &'static mut Regenerating,
+    )>,
+    life_shapes: QueryState<(
+        &'static Life,
+        &'static MaxLife,
+        &'static LifeShapes,
+        &'static mut Shape,
+    )>,
+}
+
+impl Queries {
+    fn new(world: &mut World) -> Self {
+        Self {
+            drawables: world.query(),
+            previous: world.query(),
+            player: world.query(),
+            paddles: world.query(),
+            positions: world.query(),
+            velocities: world.query(),
+            lives: world.query(),
+            damage: world.query(),
+            bounds: world.query(),
+            explosive: world.query(),
+            drops: world.query(),
+            regenerating: world.query(),
+            life_shapes: world.query(),
+        }
+    }
+}
+
+/// How the paddle aims the ball, angles are in radians from straight up.
+#[derive(Debug, Clone, Copy)]
+struct PaddleBounce {
+    /// Smallest angle, so the ball never gets stuck bouncing straight up and down.
+    min_angle: f32,
+    /// Angle when hitting the very edge of the paddle.
+    max_angle: f32,
+    /// How much of the paddle velocity is added to the ball.
+    english: f32,
+}
+
+impl Default for PaddleBounce {
+    fn default() -> Self {
+        PaddleBounce {
+            min_angle: 10f32.to_radians(),
+            max_angle: 60f32.to_radians(),
+            english: 0.25,
+        }
+    }
+}
+
+/// Where the player is in the campaign.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Playing,
+    /// Showing the level cleared screen, with the seconds left before the next level.
+    Cleared(f32),
+    GameOver,
+    /// Every level of the campaign was cleared.
+    Finished,
+}
+
+/// Source of every random choice in the game.
+struct GameRng(StdRng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        if cfg!(feature = "deterministic") {
+            GameRng(StdRng::seed_from_u64(RNG_SEED))
+        } else {
+            GameRng(StdRng::from_entropy())
+        }
+    }
+}
+
+/// Whether balls bounce off each other, they pass through each other otherwise.
+#[derive(Debug, Clone, Copy)]
+struct BallCollisions(pub bool);
+
+/// Hit points taken from a brick.
+#[derive(Debug, Clone)]
+struct Damage {
+    brick: Entity,
+    amount: u8,
 }
 
+/// Brick that ran out of hit points, despawned at the end of the step.
+#[derive(Debug, Clone)]
+struct BrickBroken {
+    brick: Entity,
+    bounds: Aabb,
+}
+
+/// Hit points left, bricks without it can't be broken.
 #[derive(Debug, Clone, Component)]
 struct Life(pub u8);
 
 #[derive(Debug, Clone, Component)]
-struct Position(pub Vec2);
+struct MaxLife(pub u8);
 
+/// Mesh for each hit point lost, the last one is kept once they run out.
 #[derive(Debug, Clone, Component)]
-struct Shape(pub Mesh);
+struct LifeShapes(pub Vec<Mesh>);
 
+/// Damage dealt to the bricks around when broken.
 #[derive(Debug, Clone, Component)]
-struct Velocity(pub Vec2);
+struct Explosive(pub u8);
 
-impl GameState {
-    pub fn new(ctx: &mut Context) -> Result<Self, GameError> {
-        let mut world = World::new();
+/// Gets a hit point back every `delay` seconds without being hit.
+#[derive(Debug, Clone, Component)]
+struct Regenerating {
+    delay: f32,
+    elapsed: f32,
+}
 
-        let player = spawn_player(&mut world, ctx)?;
-        let blocks = spawn_blocks(&mut world, ctx)?;
+/// Chance from 0 to 1 of dropping a power-up when broken.
+#[derive(Debug, Clone, Component)]
+struct PowerUpDrop(pub f32);
 
-        Ok(Self {
-            world,
-            player,
-            blocks,
-            balls: vec![],
-        })
-    }
+/// The player's paddle, `width` grows with the wide paddle power-up.
+#[derive(Debug, Clone, Component)]
+struct Paddle {
+    width: f32,
+}
+
+/// Position at the start of the current fixed step, used to interpolate drawing.
+#[derive(Debug, Clone, Component)]
+struct PreviousPosition(pub Vec2);
+
+#[derive(Debug, Clone, Component)]
+struct Shape(pub Mesh);
+
+fn setup(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let campaign = load_campaign(ctx)?;
+    let progress = load_progress(ctx, &campaign);
+
+    let player = spawn_player(world, ctx)?;
+
+    spawn_walls(world, ctx);
+
+    let queries = Queries::new(world);
+
+    world.insert_resource(GameState {
+        player,
+        blocks: vec![],
+        balls: vec![],
+        power_ups: vec![],
+        lasers: vec![],
+        queries,
+    });
+
+    world.insert_resource(campaign);
+    world.insert_resource(progress);
+
+    start_level(world, ctx)
 }
 
-impl EventHandler for GameState {
-    fn update(&mut self, ctx: &mut Context) -> Result<(), GameError> {
-        let dt = ctx.time.delta().as_secs_f32();
+/// Clears the board and spawns the level the player is at, saving the progress.
+fn start_level(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let progress = *world
+        .resource::<Progress>()
+        .expect("Could not find Progress resource");
 
-        check_player_collisions(self, ctx, dt)?;
-        check_block_collisions(self, ctx, dt)?;
-        update_player(self, ctx, dt)?;
-        update_balls(self, ctx, dt)?;
-        update_blocks(self, ctx, dt)?;
+    let path = {
+        let campaign = world
+            .resource::<Campaign>()
+            .expect("Could not find Campaign resource");
+
+        format!("{}/{}", LEVELS_DIR, campaign.levels[progress.level])
+    };
 
-        Ok(())
+    let level = load_level(ctx, &path)?;
+
+    clear_board(world);
+
+    let blocks = spawn_blocks(world, ctx, &level)?;
+
+    if let Some(mut gs) = world.resource_mut::<GameState>() {
+        gs.blocks = blocks;
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> Result<(), GameError> {
-        let mut canvas = graphics::Canvas::from_frame(ctx, Color::from([0.1, 0.2, 0.3, 1.0]));
+    world.insert_resource(level);
+    world.insert_resource(Phase::Playing);
+
+    save_progress(ctx, &progress)
+}
 
-        draw_entities(self, &mut canvas);
+/// Despawns every brick, ball, power-up and laser left, and ends the effects.
+fn clear_board(world: &mut World) {
+    power_up::clear_effects(world);
+
+    let entities = {
+        let mut state = world
+            .resource_mut::<GameState>()
+            .expect("Could not find GameState resource");
+        let gs = &mut *state;
+
+        let mut entities = std::mem::take(&mut gs.blocks);
+        entities.append(&mut gs.balls);
+        entities.append(&mut gs.power_ups);
+        entities.append(&mut gs.lasers);
+        entities
+    };
 
-        canvas.finish(ctx)
+    for entity in entities {
+        world.despawn(entity);
     }
 }
 
@@ -112,58 +389,172 @@ fn clamp(coord: &mut f32, low: f32, high: f32) {
 fn spawn_player(world: &mut World, ctx: &mut Context) -> Result<Entity, GameError> {
     let rect = Rect::new(0.0, 0.0, PLAYER_WIDTH, PLAYER_HEIGHT);
 
+    let position = vec2(
+        (ctx.gfx.size().0 * 0.5) - (PLAYER_WIDTH * 0.5),
+        ctx.gfx.size().1 - 150.0,
+    );
+
     Ok(world.spawn((
-        Position(vec2(
-            (ctx.gfx.size().0 * 0.5) - (PLAYER_WIDTH * 0.5),
-            ctx.gfx.size().1 - 150.0,
-        )),
+        Position(position),
+        PreviousPosition(position),
         Shape(Mesh::new_rectangle(
             &ctx.gfx,
             DrawMode::fill(),
             rect,
             Color::WHITE,
         )?),
-        Velocity(vec2(300.0, 0.0)),
+        Velocity(Vec2::ZERO),
+        Paddle {
+            width: PLAYER_WIDTH,
+        },
+        RigidBody::Kinematic,
+        Collider::new(Aabb::new(Vec2::ZERO, PLAYER_WIDTH, PLAYER_HEIGHT))
+            .with_layer(LAYER_PADDLE)
+            .with_mask(LAYER_BALL | LAYER_POWER_UP),
     )))
 }
 
-fn spawn_blocks(world: &mut World, ctx: &mut Context) -> Result<Vec<Entity>, GameError> {
-    let mut blocks = Vec::new();
+fn read_file(ctx: &mut Context, path: &str) -> Result<String, GameError> {
+    let mut source = String::new();
 
-    let (rows, columns) = (7, 7);
+    ctx.fs.open(path)?.read_to_string(&mut source)?;
 
-    let padding = 5.0;
-    let total_block_size = vec2(BLOCK_WIDTH, BLOCK_HEIGHT) + vec2(padding, padding);
+    Ok(source)
+}
+
+fn load_level(ctx: &mut Context, path: &str) -> Result<Level, GameError> {
+    Ok(Level::parse(&read_file(ctx, path)?)?)
+}
+
+fn load_campaign(ctx: &mut Context) -> Result<Campaign, GameError> {
+    Ok(Campaign::parse(&read_file(ctx, CAMPAIGN_FILE)?)?)
+}
+
+/// Saved progress, or the start of the campaign when there is none usable.
+fn load_progress(ctx: &mut Context, campaign: &Campaign) -> Progress {
+    let saved = read_file(ctx, PROGRESS_FILE)
+        .ok()
+        .and_then(|source| Progress::parse(&source).ok());
+
+    match saved {
+        // The campaign may have gotten shorter since it was saved.
+        Some(progress) if progress.level < campaign.levels.len() && progress.lives > 0 => {
+            Progress {
+                lives: progress.lives.min(MAX_LIVES),
+                ..progress
+            }
+        }
+        _ => Progress::new(STARTING_LIVES),
+    }
+}
+
+fn save_progress(ctx: &mut Context, progress: &Progress) -> Result<(), GameError> {
+    let mut file = ctx.fs.create(PROGRESS_FILE)?;
+
+    write!(file, "{}", progress)?;
+
+    Ok(())
+}
+
+fn spawn_blocks(
+    world: &mut World,
+    ctx: &mut Context,
+    level: &Level,
+) -> Result<Vec<Entity>, GameError> {
+    let total_block_size = vec2(BLOCK_WIDTH, BLOCK_HEIGHT) + vec2(BLOCK_PADDING, BLOCK_PADDING);
 
     let board_start_pos = vec2(
-        (ctx.gfx.size().0 - (total_block_size.x * rows as f32)) * 0.5,
+        (ctx.gfx.size().0 - (total_block_size.x * level.columns() as f32)) * 0.5,
         50.0,
     );
 
     let rect = Rect::new(0.0, 0.0, BLOCK_WIDTH, BLOCK_HEIGHT);
 
-    for i in 0..rows * columns {
-        let block_x = (i % rows) as f32 * total_block_size.x;
-        let block_y = (i / rows) as f32 * total_block_size.y;
+    let mut meshes = HashMap::new();
 
-        let entity = world.spawn((
-            Life(BLOCK_LIFE),
-            Position(board_start_pos + vec2(block_x, block_y)),
-            Shape(Mesh::new_rectangle(
-                ctx,
-                DrawMode::fill(),
-                rect,
-                Color::from_rgb(210, 180, 140),
-            )?),
-        ));
+    for (symbol, brick) in level.bricks.iter() {
+        let shapes = brick
+            .colors
+            .iter()
+            .map(|color| Mesh::new_rectangle(ctx, DrawMode::fill(), rect, *color))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        blocks.push(entity);
+        meshes.insert(*symbol, shapes);
     }
 
+    let blocks = world.spawn_batch(level.cells().map(|(column, row, symbol)| {
+        let block_x = column as f32 * total_block_size.x;
+        let block_y = row as f32 * total_block_size.y;
+
+        let brick = &level.bricks[&symbol];
+        let shapes = &meshes[&symbol];
+
+        let place = board_start_pos + vec2(block_x, block_y);
+        let path = brick
+            .path
+            .as_ref()
+            .map(|path| MotionPath::new(path.translate(place)));
+        let position = path
+            .as_ref()
+            .map_or(place, |path| math::to_vec2(path.position_at(Real::ZERO)));
+
+        (
+            brick.life.map(|life| (Life(life), MaxLife(life))),
+            Position(position),
+            Shape(shapes[0].clone()),
+            (shapes.len() > 1).then(|| LifeShapes(shapes.clone())),
+            brick.explosive.map(Explosive),
+            brick.regenerates.map(|delay| Regenerating {
+                delay,
+                elapsed: 0.0,
+            }),
+            (brick.drop_chance > 0.0).then_some(PowerUpDrop(brick.drop_chance)),
+            path.map(|path| {
+                (
+                    path,
+                    Velocity(Vec2::ZERO),
+                    PreviousPosition(position),
+                    RigidBody::Kinematic,
+                )
+            }),
+            Collider::new(Aabb::new(Vec2::ZERO, BLOCK_WIDTH, BLOCK_HEIGHT))
+                // Unbreakable bricks are walls, so fireballs bounce off them.
+                .with_layer(match brick.life {
+                    Some(_) => LAYER_BRICK,
+                    None => LAYER_WALL,
+                })
+                .with_mask(LAYER_BALL | LAYER_LASER),
+        )
+    }));
+
     Ok(blocks)
 }
 
 fn spawn_ball(world: &mut World, ctx: &mut Context) -> Result<Entity, GameError> {
+    let position = vec2(ctx.gfx.size().0 / 2.0, ctx.gfx.size().1 - 225.0);
+
+    let speed = world
+        .resource::<Level>()
+        .expect("Could not find Level resource")
+        .ball_speed;
+
+    let direction = {
+        let mut rng = world
+            .resource_mut::<GameRng>()
+            .expect("Could not find GameRng resource");
+
+        vec2(rng.0.gen_range(-1.0..1.0), rng.0.gen_range(-1.0..1.0)).normalize()
+    };
+
+    spawn_ball_at(world, ctx, position, direction * speed)
+}
+
+fn spawn_ball_at(
+    world: &mut World,
+    ctx: &mut Context,
+    position: Vec2,
+    velocity: Vec2,
+) -> Result<Entity, GameError> {
     let circle = Mesh::new_circle(
         ctx,
         DrawMode::fill(),
@@ -173,68 +564,223 @@ fn spawn_ball(world: &mut World, ctx: &mut Context) -> Result<Entity, GameError>
         Color::WHITE,
     )?;
 
+    let mut mask = LAYER_PADDLE | LAYER_BRICK | LAYER_WALL;
+
+    if world
+        .resource::<BallCollisions>()
+        .is_some_and(|balls| balls.0)
+    {
+        mask |= LAYER_BALL;
+    }
+
     Ok(world.spawn((
-        Position(vec2(
-            ctx.gfx.size().0 / 2.0 - BALL_RADIUS,
-            ctx.gfx.size().1 - 225.0,
-        )),
-        Velocity(
-            vec2(
-                rand::thread_rng().gen_range(-1.0..1.0),
-                rand::thread_rng().gen_range(-1.0..1.0),
-            )
-            .normalize(),
-        ),
+        Position(position),
+        PreviousPosition(position),
+        Velocity(velocity),
         Shape(circle),
+        RigidBody::Dynamic,
+        Restitution(1.0),
+        Collider::new(Circle::new(Vec2::ZERO, BALL_RADIUS))
+            .with_layer(LAYER_BALL)
+            .with_mask(mask),
     )))
 }
 
-fn draw_entities(gs: &mut GameState, canvas: &mut Canvas) {
-    let mut query = gs.world.query::<(&Position, &Shape)>();
+/// Invisible walls on the top and sides of the screen for the ball to bounce on.
+fn spawn_walls(world: &mut World, ctx: &mut Context) -> Vec<Entity> {
+    let (width, height) = ctx.gfx.size();
+
+    let walls = [
+        Aabb::new(
+            vec2(-WALL_THICKNESS, -WALL_THICKNESS),
+            width + WALL_THICKNESS * 2.0,
+            WALL_THICKNESS,
+        ),
+        Aabb::new(vec2(-WALL_THICKNESS, 0.0), WALL_THICKNESS, height),
+        Aabb::new(vec2(width, 0.0), WALL_THICKNESS, height),
+    ];
+
+    world.spawn_batch(walls.into_iter().map(|wall| {
+        let collider = Collider::new(wall.translate(-wall.min))
+            .with_layer(LAYER_WALL)
+            .with_mask(LAYER_BALL | LAYER_LASER);
+
+        (Position(math::to_vec2(wall.min)), collider)
+    }))
+}
 
-    match query.get(&gs.world, gs.player.clone()) {
-        (Some(position), Some(shape)) => canvas.draw(&shape.0, position.0),
+fn draw_entities(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
+    let mut canvas = world
+        .resource_mut::<Canvas>()
+        .expect("Could not find Canvas resource");
+    let alpha = world
+        .resource::<FixedTime>()
+        .expect("Could not find FixedTime resource")
+        .alpha();
+
+    let query = &mut gs.queries.drawables;
+
+    match query.get(world, gs.player.clone()) {
+        (Some(position), Some(shape), previous) => {
+            canvas.draw(&shape.0, interpolate(&position, previous, alpha))
+        }
         _ => panic!("Could not find components to draw Player"),
     }
 
     for block in gs.blocks.iter() {
-        match query.get(&gs.world, block.clone()) {
-            (Some(position), Some(shape)) => canvas.draw(&shape.0, position.0),
+        match query.get(world, block.clone()) {
+            (Some(position), Some(shape), previous) => {
+                canvas.draw(&shape.0, interpolate(&position, previous, alpha))
+            }
             _ => panic!("Could not find components to draw Block {:?}", block),
         }
     }
 
     for ball in gs.balls.iter() {
-        match query.get(&gs.world, ball.clone()) {
-            (Some(position), Some(shape)) => canvas.draw(&shape.0, position.0),
+        match query.get(world, ball.clone()) {
+            (Some(position), Some(shape), previous) => {
+                canvas.draw(&shape.0, interpolate(&position, previous, alpha))
+            }
             _ => panic!("Could not find components to draw Ball {:?}", ball),
         }
     }
+
+    for capsule in gs.power_ups.iter() {
+        match query.get(world, capsule.clone()) {
+            (Some(position), Some(shape), previous) => {
+                canvas.draw(&shape.0, interpolate(&position, previous, alpha))
+            }
+            _ => panic!("Could not find components to draw PowerUp {:?}", capsule),
+        }
+    }
+
+    for laser in gs.lasers.iter() {
+        match query.get(world, laser.clone()) {
+            (Some(position), Some(shape), previous) => {
+                canvas.draw(&shape.0, interpolate(&position, previous, alpha))
+            }
+            _ => panic!("Could not find components to draw Laser {:?}", laser),
+        }
+    }
+
+    Ok(())
+}
+
+fn draw_hud(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let mut canvas = world
+        .resource_mut::<Canvas>()
+        .expect("Could not find Canvas resource");
+    let progress = world
+        .resource::<Progress>()
+        .expect("Could not find Progress resource");
+    let level = world
+        .resource::<Level>()
+        .expect("Could not find Level resource");
+    let phase = *world
+        .resource::<Phase>()
+        .expect("Could not find Phase resource");
+
+    let status = Text::new(format!(
+        "{}    Score {}    Lives {}",
+        level.name, progress.score, progress.lives
+    ));
+
+    canvas.draw(&status, vec2(10.0, 10.0));
+
+    let message = match phase {
+        Phase::Playing => return Ok(()),
+        Phase::Cleared(_) => format!("{} cleared!", level.name),
+        Phase::GameOver => "Game over\nPress space to try again".to_string(),
+        Phase::Finished => format!(
+            "Campaign complete!\nScore {}\nPress space to play again",
+            progress.score
+        ),
+    };
+
+    let mut text = Text::new(message);
+    text.set_scale(48.0).set_layout(TextLayout::center());
+
+    let (width, height) = ctx.gfx.size();
+
+    canvas.draw(&text, vec2(width * 0.5, height * 0.5));
+
+    Ok(())
+}
+
+fn interpolate(position: &Position, previous: Option<Ref<PreviousPosition>>, alpha: f32) -> Vec2 {
+    match previous {
+        Some(previous) => previous.0.lerp(position.0, alpha),
+        None => position.0,
+    }
+}
+
+fn store_previous_positions(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
+
+    for (position, previous) in gs.queries.previous.iter(world) {
+        if let (Some(position), Some(mut previous)) = (position, previous) {
+            previous.0 = position.0;
+        }
+    }
+
+    Ok(())
 }
 
-fn update_player(gs: &mut GameState, ctx: &mut Context, dt: f32) -> Result<(), GameError> {
-    let mut query = gs.world.query::<(&mut Position, &Velocity)>();
+fn update_player(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let dt = world
+        .resource::<FixedTime>()
+        .expect("Could not find FixedTime resource")
+        .delta_secs();
+
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
 
-    match query.get(&gs.world, gs.player.clone()) {
-        (Some(mut position), Some(velocity)) => {
-            let position = position.borrow_mut();
+    let query = &mut gs.queries.player;
+
+    match query.get(world, gs.player.clone()) {
+        (Some(position), Some(mut velocity), Some(paddle)) => {
+            let mut direction = 0.0;
 
             if ctx.keyboard.is_key_pressed(KeyCode::A) {
-                position.0.x -= velocity.0.x * dt;
+                direction -= 1.0;
             }
 
             if ctx.keyboard.is_key_pressed(KeyCode::D) {
-                position.0.x += velocity.0.x * dt;
+                direction += 1.0;
             }
 
-            clamp(&mut position.0.x, 0.0, ctx.gfx.size().0 - PLAYER_WIDTH);
+            // Only ask the physics step to move the paddle as far as the edges.
+            let mut target = position.0.x + direction * PLAYER_SPEED * dt;
+
+            clamp(&mut target, 0.0, ctx.gfx.size().0 - paddle.width);
+
+            velocity.0.x = (target - position.0.x) / dt;
         }
         _ => panic!("Could not find components to update Player"),
     }
 
-    if ctx.keyboard.is_key_pressed(KeyCode::Space) {
-        if gs.balls.len() < 1 {
-            let ball = spawn_ball(&mut gs.world, ctx)?;
+    let playing = world
+        .resource::<Phase>()
+        .is_some_and(|phase| *phase == Phase::Playing);
+
+    let should_spawn_ball =
+        playing && ctx.keyboard.is_key_pressed(KeyCode::Space) && gs.balls.is_empty();
+
+    drop(state);
+
+    if should_spawn_ball {
+        let ball = spawn_ball(world, ctx)?;
+
+        if let Some(mut gs) = world.resource_mut::<GameState>() {
             gs.balls.push(ball);
         }
     }
@@ -242,164 +788,497 @@ fn update_player(gs: &mut GameState, ctx: &mut Context, dt: f32) -> Result<(), G
     Ok(())
 }
 
-fn update_blocks(gs: &mut GameState, _ctx: &mut Context, _dt: f32) -> Result<(), GameError> {
-    let mut query = gs.world.query::<&Life>();
+/// Breaks the bricks left without hit points.
+fn update_blocks(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
 
-    let mut should_destroy = Vec::new();
+    let mut broken = world
+        .resource_mut::<Events<BrickBroken>>()
+        .expect("Could not find BrickBroken events");
+
+    broken.clear();
 
     for block in gs.blocks.iter() {
-        match query.get(&gs.world, block.clone()) {
-            Some(life) => {
-                if life.0 == 0 {
-                    should_destroy.push(block.clone());
-                    // TODO: also remove from world
-                }
-            }
-            None => panic!("Could not find Block({:?}) life component", block),
+        let out_of_life = gs
+            .queries
+            .lives
+            .get(world, block.clone())
+            .is_some_and(|life| life.0 == 0);
+
+        if !out_of_life {
+            continue;
+        }
+
+        match gs.queries.bounds.get(world, block.clone()) {
+            (Some(position), Some(collider)) => broken.send(BrickBroken {
+                brick: block.clone(),
+                bounds: collider.place(position.0, 0.0).bounds(),
+            }),
+            _ => panic!("Could not find components to break Block {:?}", block),
         }
     }
 
-    gs.blocks.retain(|block| !should_destroy.contains(block));
+    gs.blocks
+        .retain(|block| !broken.iter().any(|broken| broken.brick == *block));
+
+    if let Some(mut progress) = world.resource_mut::<Progress>() {
+        progress.score += broken.len() as u32 * BRICK_SCORE;
+    }
 
     Ok(())
 }
 
-fn update_balls(gs: &mut GameState, ctx: &mut Context, dt: f32) -> Result<(), GameError> {
-    let mut query = gs.world.query::<(&mut Position, &mut Velocity)>();
+/// Takes the hit points sent this step off the bricks.
+fn damage_bricks(world: &mut World) -> Result<(), GameError> {
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
 
-    let mut should_destroy = Vec::new();
+    let mut damage = world
+        .resource_mut::<Events<Damage>>()
+        .expect("Could not find Damage events");
 
-    for ball in gs.balls.iter() {
-        match query.get(&gs.world, ball.clone()) {
-            (Some(mut position), Some(mut velocity)) => {
-                position.0.x += velocity.0.x * BALL_SPEED * dt;
-                position.0.y += velocity.0.y * BALL_SPEED * dt;
+    for Damage { brick, amount } in damage.drain() {
+        let (Some(mut life), regenerating) = gs.queries.damage.get(world, brick) else {
+            continue;
+        };
 
-                if position.0.x < BALL_RADIUS {
-                    velocity.0.x = 1.0;
-                }
+        life.0 = life.0.saturating_sub(amount);
 
-                if position.0.x > ctx.gfx.size().0 - BALL_RADIUS {
-                    velocity.0.x = -1.0;
-                }
+        if let Some(mut regenerating) = regenerating {
+            regenerating.elapsed = 0.0;
+        }
+    }
 
-                if position.0.y < BALL_RADIUS {
-                    velocity.0.y = 1.0;
-                }
+    Ok(())
+}
 
-                if position.0.y < ctx.gfx.size().1 {
-                    should_destroy.push(ball.clone());
-                    // TODO: also remove from world
+/// Damages the bricks touching the explosive bricks that just broke.
+fn explode_bricks(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
+
+    let broken = world
+        .resource::<Events<BrickBroken>>()
+        .expect("Could not find BrickBroken events");
+    let grid = world
+        .resource::<SpatialGrid>()
+        .expect("Could not find SpatialGrid resource");
+    let mut damage = world
+        .resource_mut::<Events<Damage>>()
+        .expect("Could not find Damage events");
+
+    for BrickBroken { brick, bounds } in broken.iter() {
+        let Some(explosive) = gs.queries.explosive.get(world, brick.clone()) else {
+            continue;
+        };
+
+        // Reaches the neighbours across the padding, diagonals included. They
+        // take the damage next step, so chains ripple out.
+        for neighbour in grid.query_region(&bounds.inflate(math::real(BLOCK_PADDING))) {
+            if neighbour != *brick && gs.blocks.contains(&neighbour) {
+                damage.send(Damage {
+                    brick: neighbour,
+                    amount: explosive.0,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Drops a power-up capsule from the bricks that just broke, by their chance.
+fn drop_power_ups(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let mut dropped = Vec::new();
+
+    {
+        let mut state = world
+            .resource_mut::<GameState>()
+            .expect("Could not find GameState resource");
+        let gs = &mut *state;
+
+        let broken = world
+            .resource::<Events<BrickBroken>>()
+            .expect("Could not find BrickBroken events");
+        let mut rng = world
+            .resource_mut::<GameRng>()
+            .expect("Could not find GameRng resource");
+
+        for BrickBroken { brick, bounds } in broken.iter() {
+            if let Some(drop) = gs.queries.drops.get(world, brick.clone()) {
+                if rng.0.gen::<f32>() < drop.0 {
+                    dropped.push((math::to_vec2(bounds.center()), PowerUp::random(&mut rng.0)));
                 }
             }
-            _ => panic!("Could not find components to update Player"),
         }
     }
 
-    gs.balls.retain(|ball| should_destroy.contains(ball));
+    for (center, power_up) in dropped {
+        let capsule = power_up::spawn_capsule(world, ctx, center, power_up)?;
+
+        if let Some(mut gs) = world.resource_mut::<GameState>() {
+            gs.power_ups.push(capsule);
+        }
+    }
 
     Ok(())
 }
 
-fn check_player_collisions(
-    gs: &mut GameState,
-    _ctx: &mut Context,
-    _dt: f32,
-) -> Result<(), GameError> {
-    let mut query = gs.world.query::<(&mut Position, &mut Velocity)>();
+fn despawn_broken_bricks(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let broken: Vec<Entity> = world
+        .resource::<Events<BrickBroken>>()
+        .expect("Could not find BrickBroken events")
+        .iter()
+        .map(|broken| broken.brick.clone())
+        .collect();
 
-    let player_position = match query.get(&gs.world, gs.player.clone()) {
-        (Some(position), Some(_)) => position,
-        _ => panic!("Could not find components to check Player collisions"),
-    };
+    for brick in broken {
+        world.despawn(brick);
+    }
+
+    Ok(())
+}
+
+fn regenerate_bricks(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let dt = world
+        .resource::<FixedTime>()
+        .expect("Could not find FixedTime resource")
+        .delta_secs();
+
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
+
+    for block in gs.blocks.iter() {
+        let (Some(mut life), Some(max), Some(mut regenerating)) =
+            gs.queries.regenerating.get(world, block.clone())
+        else {
+            continue;
+        };
+
+        if life.0 >= max.0 {
+            continue;
+        }
+
+        regenerating.elapsed += dt;
+
+        if regenerating.elapsed >= regenerating.delay {
+            regenerating.elapsed = 0.0;
+            life.0 += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Shows how many hit points the bricks have left.
+fn update_brick_shapes(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
+
+    for block in gs.blocks.iter() {
+        let (Some(life), Some(max), Some(shapes), Some(mut shape)) =
+            gs.queries.life_shapes.get(world, block.clone())
+        else {
+            continue;
+        };
+
+        let lost = max.0.saturating_sub(life.0) as usize;
+
+        shape.0 = shapes.0[lost.min(shapes.0.len() - 1)].clone();
+    }
+
+    Ok(())
+}
+
+fn update_balls(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
+
+    let query = &mut gs.queries.positions;
+
+    let mut should_destroy = Vec::new();
 
     for ball in gs.balls.iter() {
-        match query.get(&gs.world, ball.clone()) {
-            (Some(mut position), Some(mut velocity)) => {
-                if let Some((_, _, w, h)) = geometry::intersection(
-                    position.0,
-                    BALL_RADIUS,
-                    BALL_RADIUS,
-                    player_position.0,
-                    PLAYER_WIDTH,
-                    PLAYER_HEIGHT,
-                ) {
-                    let (ball_position, ball_velocity) = collision::aabb(
-                        position.0,
-                        BALL_RADIUS,
-                        BALL_RADIUS,
-                        velocity.0,
-                        player_position.0,
-                        PLAYER_WIDTH,
-                        PLAYER_HEIGHT,
-                        w,
-                        h,
-                    );
-
-                    position.0.x = ball_position.x;
-                    position.0.y = ball_position.y;
-
-                    velocity.0.x = ball_velocity.x;
-                    velocity.0.y = ball_velocity.y;
+        match query.get(world, ball.clone()) {
+            Some(position) => {
+                if position.0.y > ctx.gfx.size().1 + BALL_RADIUS {
+                    should_destroy.push(ball.clone());
                 }
             }
             _ => panic!("Could not find components to update Ball {:?}", ball),
         }
     }
 
+    gs.balls.retain(|ball| !should_destroy.contains(ball));
+
+    let lost_every_ball = !should_destroy.is_empty() && gs.balls.is_empty();
+
+    drop(state);
+
+    for ball in should_destroy {
+        world.despawn(ball);
+    }
+
+    if lost_every_ball {
+        power_up::clear_effects(world);
+
+        let mut progress = world
+            .resource_mut::<Progress>()
+            .expect("Could not find Progress resource");
+
+        progress.lives = progress.lives.saturating_sub(1);
+
+        if progress.lives == 0 {
+            if let Some(mut phase) = world.resource_mut::<Phase>() {
+                *phase = Phase::GameOver;
+            }
+        }
+    }
+
     Ok(())
 }
 
-fn check_block_collisions(
-    gs: &mut GameState,
-    _ctx: &mut Context,
-    _dt: f32,
-) -> Result<(), GameError> {
-    let mut ball_query = gs.world.query::<(&mut Position, &mut Velocity)>();
-    let mut block_query = gs.world.query::<(&Position, &mut Life)>();
+/// Shows the level cleared screen once every brick that can be broken is gone.
+fn check_level_cleared(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let cleared = world.resource_mut::<GameState>().is_some_and(|mut state| {
+        let gs = &mut *state;
 
-    for ball in gs.balls.iter() {
-        match ball_query.get(&gs.world, ball.clone()) {
-            (Some(mut position), Some(mut velocity)) => {
-                for block in gs.blocks.iter() {
-                    match block_query.get(&gs.world, block.clone()) {
-                        (Some(block_position), Some(mut life)) => {
-                            if let Some((_, _, w, h)) = geometry::intersection(
-                                position.0,
-                                BALL_RADIUS,
-                                BALL_RADIUS,
-                                block_position.0,
-                                BLOCK_WIDTH,
-                                BLOCK_HEIGHT,
-                            ) {
-                                let (ball_position, ball_velocity) = collision::aabb(
-                                    position.0,
-                                    BALL_RADIUS,
-                                    BALL_RADIUS,
-                                    velocity.0,
-                                    block_position.0,
-                                    BLOCK_WIDTH,
-                                    BLOCK_HEIGHT,
-                                    w,
-                                    h,
-                                );
-
-                                position.0.x = ball_position.x;
-                                position.0.y = ball_position.y;
-
-                                velocity.0.x = ball_velocity.x;
-                                velocity.0.y = ball_velocity.y;
-
-                                life.0 -= 1;
-                            }
-                        }
-                        _ => panic!("Could not find block components"),
-                    }
+        gs.blocks
+            .iter()
+            .all(|block| gs.queries.lives.get(world, block.clone()).is_none())
+    }) && world
+        .resource::<Phase>()
+        .is_some_and(|phase| *phase == Phase::Playing);
+
+    if !cleared {
+        return Ok(());
+    }
+
+    clear_board(world);
+
+    let finished = {
+        let campaign = world
+            .resource::<Campaign>()
+            .expect("Could not find Campaign resource");
+        let mut progress = world
+            .resource_mut::<Progress>()
+            .expect("Could not find Progress resource");
+
+        if campaign.is_last(progress.level) {
+            true
+        } else {
+            progress.level += 1;
+            false
+        }
+    };
+
+    if finished {
+        // The next session starts a new campaign.
+        save_progress(ctx, &Progress::new(STARTING_LIVES))?;
+
+        world.insert_resource(Phase::Finished);
+    } else {
+        world.insert_resource(Phase::Cleared(TRANSITION_SECS));
+    }
+
+    Ok(())
+}
+
+/// Moves on from the level cleared, game over and campaign finished screens.
+fn update_transition(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let phase = *world
+        .resource::<Phase>()
+        .expect("Could not find Phase resource");
+
+    let should_start = match phase {
+        Phase::Playing => false,
+        Phase::Cleared(remaining) => {
+            let remaining = remaining - ctx.time.delta().as_secs_f32();
+
+            world.insert_resource(Phase::Cleared(remaining));
+
+            remaining <= 0.0
+        }
+        Phase::GameOver | Phase::Finished => ctx.keyboard.is_key_just_pressed(KeyCode::Space),
+    };
+
+    if !should_start {
+        return Ok(());
+    }
+
+    if let Some(mut progress) = world.resource_mut::<Progress>() {
+        match phase {
+            // Try the same level again from scratch.
+            Phase::GameOver => {
+                *progress = Progress {
+                    level: progress.level,
+                    ..Progress::new(STARTING_LIVES)
                 }
             }
-            _ => panic!("Could not find components to check ball collisions"),
+            Phase::Finished => *progress = Progress::new(STARTING_LIVES),
+            _ => {}
         }
     }
 
+    start_level(world, ctx)
+}
+
+/// Aims the balls bouncing on top of the paddle by where they hit it.
+fn bounce_off_paddle(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let bounce = *world
+        .resource::<PaddleBounce>()
+        .expect("Could not find PaddleBounce resource");
+
+    let mut state = world
+        .resource_mut::<GameState>()
+        .expect("Could not find GameState resource");
+    let gs = &mut *state;
+
+    let started = world
+        .resource::<Events<CollisionStarted>>()
+        .expect("Could not find CollisionStarted events");
+
+    for CollisionStarted(collision) in started.iter() {
+        if collision.b != gs.player || !gs.balls.contains(&collision.a) {
+            continue;
+        }
+
+        // Only the top of the paddle aims, the sides just reflect.
+        if collision.contact.normal.y >= 0.0 {
+            continue;
+        }
+
+        let (Some(ball), Some(paddle)) = (
+            gs.queries.positions.get(world, collision.a.clone()),
+            gs.queries.positions.get(world, collision.b.clone()),
+        ) else {
+            continue;
+        };
+
+        let paddle_velocity = match gs.queries.velocities.get(world, collision.b.clone()) {
+            Some(velocity) => velocity.0,
+            None => Vec2::ZERO,
+        };
+
+        let Some(mut velocity) = gs.queries.velocities.get(world, collision.a.clone()) else {
+            continue;
+        };
+
+        let half_width = match gs.queries.paddles.get(world, collision.b.clone()) {
+            Some(paddle) => paddle.width * 0.5,
+            None => PLAYER_WIDTH * 0.5,
+        };
+        let offset = ((ball.0.x - paddle.0.x - half_width) / half_width).clamp(-1.0, 1.0);
+
+        let speed = velocity.0.length();
+        let angle = offset * bounce.max_angle;
+
+        let aimed = upwards(angle) * speed + paddle_velocity * bounce.english;
+        let angle = math::atan2(aimed.x, -aimed.y);
+
+        let side = if angle < 0.0 { -1.0 } else { 1.0 };
+        let angle = side * angle.abs().clamp(bounce.min_angle, bounce.max_angle);
+
+        // Keep the speed, only the direction is aimed.
+        velocity.0 = upwards(angle) * speed;
+    }
+
     Ok(())
 }
+
+/// Unit vector `angle` radians clockwise from straight up.
+fn upwards(angle: f32) -> Vec2 {
+    let (sin, cos) = math::sin_cos(angle);
+
+    vec2(sin, -cos)
+}
+
+/// Damages the bricks balls bounced off, lasers are left to `check_laser_hits`.
+fn check_block_collisions(world: &mut World) -> Result<(), GameError> {
+    let state = world
+        .resource::<GameState>()
+        .expect("Could not find GameState resource");
+
+    let started = world
+        .resource::<Events<CollisionStarted>>()
+        .expect("Could not find CollisionStarted events");
+    let mut damage = world
+        .resource_mut::<Events<Damage>>()
+        .expect("Could not find Damage events");
+
+    for CollisionStarted(collision) in started.iter() {
+        if state.blocks.contains(&collision.b) && state.balls.contains(&collision.a) {
+            damage.send(Damage {
+                brick: collision.b.clone(),
+                amount: 1,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use breakout::math::Vector;
+    use breakout::physics::collision::Contact;
+    use breakout::physics::step::Collision;
+
+    use super::*;
+
+    #[test]
+    fn laser_hits_take_one_hit_point() {
+        let mut world = World::new();
+
+        let player = world.spawn(Position(Vec2::ZERO));
+        let brick = world.spawn((Position(Vec2::ZERO), Life(2), MaxLife(2)));
+        let laser = world.spawn(Position(Vec2::ZERO));
+
+        let queries = Queries::new(&mut world);
+
+        world.insert_resource(GameState {
+            player,
+            blocks: vec![brick.clone()],
+            balls: vec![],
+            power_ups: vec![],
+            lasers: vec![laser.clone()],
+            queries,
+        });
+        world.insert_resource(Events::<Damage>::new());
+
+        let mut started = Events::<CollisionStarted>::new();
+        started.send(CollisionStarted(Collision {
+            a: laser.clone(),
+            b: brick.clone(),
+            contact: Contact {
+                normal: math::vec2(0.0, 1.0),
+                depth: Real::ONE,
+                point: Vector::ZERO,
+            },
+            sensor: true,
+        }));
+        world.insert_resource(started);
+
+        check_block_collisions(&mut world).unwrap();
+        power_up::check_laser_hits(&mut world).unwrap();
+        damage_bricks(&mut world).unwrap();
+
+        let mut lives = world.query::<&Life>();
+
+        assert_eq!(lives.get(&world, brick).map(|life| life.0), Some(1));
+        assert!(lives.get(&world, laser).is_none());
+    }
+}
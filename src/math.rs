@@ -0,0 +1,838 @@
+//! Scalar and vector math the simulation goes through instead of `f32` and
+//! [`Vec2`].
+//!
+//! The geometry, the collisions and the physics step compute with [`Real`]
+//! and [`Vector`], which are plain `f32` and [`Vec2`] by default. With the
+//! `deterministic` feature they are the 48.16 [`Fixed`] and [`FixedVec2`]
+//! instead, so the simulation only does integer arithmetic and replays bit for
+//! bit from the same seed and inputs, whichever compiler or platform built it.
+//!
+//! Components stay in `f32` and go in and out of the simulation through
+//! [`real`], [`vector`], [`to_f32`] and [`to_vec2`], which round the same way
+//! everywhere. The `f32` trigonometry below, used by the gameplay, is computed
+//! with [`Fixed`] too instead of the platform `libm`.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Div;
+use std::ops::DivAssign;
+use std::ops::Index;
+use std::ops::IndexMut;
+use std::ops::Mul;
+use std::ops::MulAssign;
+use std::ops::Neg;
+use std::ops::Sub;
+use std::ops::SubAssign;
+
+use ggez::glam::Vec2;
+
+/// Pi and pi/2 with 32 fractional bits, for range reduction.
+const PI_WIDE: i128 = 13_493_037_705;
+const FRAC_PI_2_WIDE: i128 = 6_746_518_852;
+
+/// Scalar the simulation computes with.
+#[cfg(not(feature = "deterministic"))]
+pub type Real = f32;
+
+/// Vector the simulation computes with.
+#[cfg(not(feature = "deterministic"))]
+pub type Vector = Vec2;
+
+#[cfg(feature = "deterministic")]
+pub type Real = Fixed;
+
+#[cfg(feature = "deterministic")]
+pub type Vector = FixedVec2;
+
+/// Constants both kinds of [`Real`] have.
+pub trait Scalar {
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+impl Scalar for f32 {
+    const ZERO: f32 = 0.0;
+    const ONE: f32 = 1.0;
+}
+
+impl Scalar for Fixed {
+    const ZERO: Fixed = Fixed(0);
+    const ONE: Fixed = Fixed(1 << Fixed::FRACTION_BITS);
+}
+
+/// Signed 48.16 fixed point number, saturating instead of wrapping on overflow.
+///
+/// Mirrors the `f32` methods the simulation uses, and mixes with `f32`
+/// operands by rounding them to the grid first, so the same code compiles
+/// with either kind of [`Real`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Fixed(pub i64);
+
+impl Fixed {
+    pub const FRACTION_BITS: u32 = 16;
+
+    pub const MIN: Fixed = Fixed(i64::MIN);
+    pub const MAX: Fixed = Fixed(i64::MAX);
+    /// Stand-ins for the `f32` infinities, arithmetic saturates at them.
+    pub const INFINITY: Fixed = Fixed::MAX;
+    pub const NEG_INFINITY: Fixed = Fixed::MIN;
+    pub const PI: Fixed = Fixed(205_887);
+    pub const FRAC_PI_2: Fixed = Fixed(102_944);
+    pub const FRAC_PI_4: Fixed = Fixed(51_472);
+
+    const SCALE: f32 = (1u32 << Fixed::FRACTION_BITS) as f32;
+
+    /// Rounds to the nearest representable number, saturating out of range.
+    #[inline]
+    pub fn from_f32(value: f32) -> Fixed {
+        Fixed((value * Fixed::SCALE).round() as i64)
+    }
+
+    /// Nearest `f32`, dividing by a power of two rounds the same everywhere.
+    #[inline]
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Fixed::SCALE
+    }
+
+    #[inline]
+    pub fn abs(self) -> Fixed {
+        Fixed(self.0.saturating_abs())
+    }
+
+    /// -1 for negative numbers and 1 otherwise, zero included like `f32::signum`.
+    #[inline]
+    pub fn signum(self) -> Fixed {
+        if self.0 < 0 {
+            -Fixed::ONE
+        } else {
+            Fixed::ONE
+        }
+    }
+
+    #[inline]
+    pub fn floor(self) -> Fixed {
+        Fixed(self.0 & !(Fixed::ONE.0 - 1))
+    }
+
+    /// The part after the point, keeping the sign like `f32::fract`.
+    #[inline]
+    pub fn fract(self) -> Fixed {
+        Fixed(self.0 % Fixed::ONE.0)
+    }
+
+    #[inline]
+    pub fn rem_euclid(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.rem_euclid(rhs.0))
+    }
+
+    /// Square root rounded down, 0 for negative numbers.
+    pub fn sqrt(self) -> Fixed {
+        if self.0 <= 0 {
+            return Fixed::ZERO;
+        }
+
+        Fixed(((self.0 as u128) << Fixed::FRACTION_BITS).isqrt() as i64)
+    }
+
+    /// Whether the number has not saturated.
+    #[inline]
+    pub fn is_finite(self) -> bool {
+        self != Fixed::INFINITY && self != Fixed::NEG_INFINITY
+    }
+
+    #[inline]
+    pub fn total_cmp(&self, other: &Fixed) -> Ordering {
+        self.cmp(other)
+    }
+
+    /// Clamps a wider intermediate result into range.
+    #[inline]
+    fn saturate(value: i128) -> Fixed {
+        Fixed(value.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+    }
+
+    /// Within 5e-5 of the `f32` sine.
+    pub fn sin(self) -> Fixed {
+        Fixed::sin_wide((self.0 as i128) << Fixed::FRACTION_BITS)
+    }
+
+    /// Within 5e-5 of the `f32` cosine.
+    #[inline]
+    pub fn cos(self) -> Fixed {
+        Fixed::sin_wide(((self.0 as i128) << Fixed::FRACTION_BITS) + FRAC_PI_2_WIDE)
+    }
+
+    #[inline]
+    pub fn sin_cos(self) -> (Fixed, Fixed) {
+        (self.sin(), self.cos())
+    }
+
+    /// Sine of a 32.32 angle, reduced at that precision so large angles do not
+    /// pick up the rounding of pi once per turn.
+    fn sin_wide(angle: i128) -> Fixed {
+        // Bring the angle to [-pi, pi], then to [-pi/2, pi/2] where the series
+        // is accurate, using sin(pi - x) = sin(x).
+        let mut x = (angle + PI_WIDE).rem_euclid(2 * PI_WIDE) - PI_WIDE;
+
+        if x > FRAC_PI_2_WIDE {
+            x = PI_WIDE - x;
+        } else if x < -FRAC_PI_2_WIDE {
+            x = -PI_WIDE - x;
+        }
+
+        let half = 1 << (Fixed::FRACTION_BITS - 1);
+        let x = Fixed(((x + half) >> Fixed::FRACTION_BITS) as i64);
+        let x2 = x * x;
+
+        // x - x^3/3! + x^5/5! - x^7/7! + x^9/9!
+        let mut term = x;
+        let mut sum = x;
+
+        for divisor in [6, 20, 42, 72] {
+            term = -(term * x2) / Fixed(divisor << Fixed::FRACTION_BITS);
+            sum += term;
+        }
+
+        sum
+    }
+
+    /// Angle of the point (`x`, `self`), within 0.002 radians of the `f32` one.
+    pub fn atan2(self, x: Fixed) -> Fixed {
+        let y = self;
+
+        if x == Fixed::ZERO && y == Fixed::ZERO {
+            return Fixed::ZERO;
+        }
+
+        let (ax, ay) = (x.abs(), y.abs());
+
+        // atan on [0, 1], mirrored into the other octants.
+        let angle = if ax >= ay {
+            atan_unit(ay / ax)
+        } else {
+            Fixed::FRAC_PI_2 - atan_unit(ax / ay)
+        };
+
+        let angle = if x < Fixed::ZERO {
+            Fixed::PI - angle
+        } else {
+            angle
+        };
+
+        if y < Fixed::ZERO {
+            -angle
+        } else {
+            angle
+        }
+    }
+}
+
+/// atan(z) for z in [0, 1].
+fn atan_unit(z: Fixed) -> Fixed {
+    // pi/4 z + z (1 - z) (0.2447 + 0.0663 z)
+    let correction = Fixed::from_f32(0.2447) + Fixed::from_f32(0.0663) * z;
+
+    Fixed::FRAC_PI_4 * z + z * (Fixed::ONE - z) * correction
+}
+
+impl From<f32> for Fixed {
+    #[inline]
+    fn from(value: f32) -> Self {
+        Fixed::from_f32(value)
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_f32(), f)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    #[inline]
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    #[inline]
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    #[inline]
+    fn mul(self, rhs: Fixed) -> Fixed {
+        Fixed::saturate((self.0 as i128 * rhs.0 as i128) >> Fixed::FRACTION_BITS)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+
+    /// Dividing by zero saturates like the `f32` infinities, 0 / 0 being 0.
+    #[inline]
+    fn div(self, rhs: Fixed) -> Fixed {
+        if rhs.0 == 0 {
+            return match self.0.cmp(&0) {
+                Ordering::Less => Fixed::NEG_INFINITY,
+                Ordering::Equal => Fixed::ZERO,
+                Ordering::Greater => Fixed::INFINITY,
+            };
+        }
+
+        Fixed::saturate(((self.0 as i128) << Fixed::FRACTION_BITS) / rhs.0 as i128)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    #[inline]
+    fn neg(self) -> Fixed {
+        Fixed(self.0.saturating_neg())
+    }
+}
+
+/// Arithmetic and comparisons against `f32`, mostly for literals, and the
+/// assigning operators.
+macro_rules! impl_fixed_ops {
+    ($($op:ident $method:ident $assign:ident $assign_method:ident),*) => {$(
+        impl $op<f32> for Fixed {
+            type Output = Fixed;
+
+            #[inline]
+            fn $method(self, rhs: f32) -> Fixed {
+                $op::$method(self, Fixed::from_f32(rhs))
+            }
+        }
+
+        impl $op<Fixed> for f32 {
+            type Output = Fixed;
+
+            #[inline]
+            fn $method(self, rhs: Fixed) -> Fixed {
+                $op::$method(Fixed::from_f32(self), rhs)
+            }
+        }
+
+        impl $assign for Fixed {
+            #[inline]
+            fn $assign_method(&mut self, rhs: Fixed) {
+                *self = $op::$method(*self, rhs);
+            }
+        }
+    )*};
+}
+
+impl_fixed_ops!(
+    Add add AddAssign add_assign,
+    Sub sub SubAssign sub_assign,
+    Mul mul MulAssign mul_assign,
+    Div div DivAssign div_assign
+);
+
+impl PartialEq<f32> for Fixed {
+    #[inline]
+    fn eq(&self, other: &f32) -> bool {
+        *self == Fixed::from_f32(*other)
+    }
+}
+
+impl PartialEq<Fixed> for f32 {
+    #[inline]
+    fn eq(&self, other: &Fixed) -> bool {
+        Fixed::from_f32(*self) == *other
+    }
+}
+
+impl PartialOrd<f32> for Fixed {
+    #[inline]
+    fn partial_cmp(&self, other: &f32) -> Option<Ordering> {
+        Some(self.cmp(&Fixed::from_f32(*other)))
+    }
+}
+
+impl PartialOrd<Fixed> for f32 {
+    #[inline]
+    fn partial_cmp(&self, other: &Fixed) -> Option<Ordering> {
+        Some(Fixed::from_f32(*self).cmp(other))
+    }
+}
+
+/// Vector of two [`Fixed`], with the [`Vec2`] methods the simulation uses.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct FixedVec2 {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl FixedVec2 {
+    pub const ZERO: FixedVec2 = FixedVec2::splat(Fixed::ZERO);
+    pub const ONE: FixedVec2 = FixedVec2::splat(Fixed::ONE);
+    pub const X: FixedVec2 = FixedVec2::new(Fixed::ONE, Fixed::ZERO);
+    pub const Y: FixedVec2 = FixedVec2::new(Fixed::ZERO, Fixed::ONE);
+
+    #[inline]
+    pub const fn new(x: Fixed, y: Fixed) -> FixedVec2 {
+        FixedVec2 { x, y }
+    }
+
+    #[inline]
+    pub const fn splat(value: Fixed) -> FixedVec2 {
+        FixedVec2::new(value, value)
+    }
+
+    #[inline]
+    pub fn from_vec2(value: Vec2) -> FixedVec2 {
+        FixedVec2::new(Fixed::from_f32(value.x), Fixed::from_f32(value.y))
+    }
+
+    #[inline]
+    pub fn to_vec2(self) -> Vec2 {
+        Vec2::new(self.x.to_f32(), self.y.to_f32())
+    }
+
+    /// Unit vector pointing at `angle` radians.
+    #[inline]
+    pub fn from_angle(angle: Fixed) -> FixedVec2 {
+        let (sin, cos) = angle.sin_cos();
+
+        FixedVec2::new(cos, sin)
+    }
+
+    #[inline]
+    pub fn dot(self, rhs: FixedVec2) -> Fixed {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    #[inline]
+    pub fn perp(self) -> FixedVec2 {
+        FixedVec2::new(-self.y, self.x)
+    }
+
+    #[inline]
+    pub fn perp_dot(self, rhs: FixedVec2) -> Fixed {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    /// `rhs` turned by the angle of `self`, scaled by its length.
+    #[inline]
+    pub fn rotate(self, rhs: FixedVec2) -> FixedVec2 {
+        FixedVec2::new(
+            self.x * rhs.x - self.y * rhs.y,
+            self.y * rhs.x + self.x * rhs.y,
+        )
+    }
+
+    #[inline]
+    pub fn length_squared(self) -> Fixed {
+        self.dot(self)
+    }
+
+    /// Computed at twice the precision, so short vectors still have a length.
+    #[inline]
+    pub fn length(self) -> Fixed {
+        let (x, y) = (self.x.0 as i128, self.y.0 as i128);
+
+        Fixed::saturate((x * x).saturating_add(y * y).isqrt())
+    }
+
+    #[inline]
+    pub fn distance_squared(self, rhs: FixedVec2) -> Fixed {
+        (self - rhs).length_squared()
+    }
+
+    #[inline]
+    pub fn distance(self, rhs: FixedVec2) -> Fixed {
+        (self - rhs).length()
+    }
+
+    pub fn normalize_or_zero(self) -> FixedVec2 {
+        let length = self.length();
+
+        if length == Fixed::ZERO {
+            return FixedVec2::ZERO;
+        }
+
+        self / length
+    }
+
+    #[inline]
+    pub fn lerp(self, rhs: FixedVec2, s: Fixed) -> FixedVec2 {
+        self + (rhs - self) * s
+    }
+
+    #[inline]
+    pub fn min(self, rhs: FixedVec2) -> FixedVec2 {
+        FixedVec2::new(self.x.min(rhs.x), self.y.min(rhs.y))
+    }
+
+    #[inline]
+    pub fn max(self, rhs: FixedVec2) -> FixedVec2 {
+        FixedVec2::new(self.x.max(rhs.x), self.y.max(rhs.y))
+    }
+
+    #[inline]
+    pub fn clamp(self, min: FixedVec2, max: FixedVec2) -> FixedVec2 {
+        FixedVec2::new(self.x.clamp(min.x, max.x), self.y.clamp(min.y, max.y))
+    }
+
+    #[inline]
+    pub fn abs(self) -> FixedVec2 {
+        FixedVec2::new(self.x.abs(), self.y.abs())
+    }
+
+    #[inline]
+    pub fn signum(self) -> FixedVec2 {
+        FixedVec2::new(self.x.signum(), self.y.signum())
+    }
+
+    #[inline]
+    pub fn max_element(self) -> Fixed {
+        self.x.max(self.y)
+    }
+
+    /// Whether both components are at most `max_abs_diff` apart.
+    #[inline]
+    pub fn abs_diff_eq(self, rhs: FixedVec2, max_abs_diff: f32) -> bool {
+        let difference = (self - rhs).abs();
+
+        difference.x <= max_abs_diff && difference.y <= max_abs_diff
+    }
+}
+
+impl From<Vec2> for FixedVec2 {
+    #[inline]
+    fn from(value: Vec2) -> Self {
+        FixedVec2::from_vec2(value)
+    }
+}
+
+impl fmt::Display for FixedVec2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {}]", self.x, self.y)
+    }
+}
+
+impl Index<usize> for FixedVec2 {
+    type Output = Fixed;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Fixed {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("FixedVec2 index out of bounds: {index}"),
+        }
+    }
+}
+
+impl IndexMut<usize> for FixedVec2 {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Fixed {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("FixedVec2 index out of bounds: {index}"),
+        }
+    }
+}
+
+impl Neg for FixedVec2 {
+    type Output = FixedVec2;
+
+    #[inline]
+    fn neg(self) -> FixedVec2 {
+        FixedVec2::new(-self.x, -self.y)
+    }
+}
+
+impl Sum for FixedVec2 {
+    fn sum<I: Iterator<Item = FixedVec2>>(iter: I) -> Self {
+        iter.fold(FixedVec2::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a FixedVec2> for FixedVec2 {
+    fn sum<I: Iterator<Item = &'a FixedVec2>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+/// Component wise arithmetic between vectors, and with a scalar on either
+/// side.
+macro_rules! impl_vector_ops {
+    ($($op:ident $method:ident $assign:ident $assign_method:ident),*) => {$(
+        impl $op for FixedVec2 {
+            type Output = FixedVec2;
+
+            #[inline]
+            fn $method(self, rhs: FixedVec2) -> FixedVec2 {
+                FixedVec2::new($op::$method(self.x, rhs.x), $op::$method(self.y, rhs.y))
+            }
+        }
+
+        impl $op<Fixed> for FixedVec2 {
+            type Output = FixedVec2;
+
+            #[inline]
+            fn $method(self, rhs: Fixed) -> FixedVec2 {
+                $op::$method(self, FixedVec2::splat(rhs))
+            }
+        }
+
+        impl $op<f32> for FixedVec2 {
+            type Output = FixedVec2;
+
+            #[inline]
+            fn $method(self, rhs: f32) -> FixedVec2 {
+                $op::$method(self, Fixed::from_f32(rhs))
+            }
+        }
+
+        impl $op<FixedVec2> for Fixed {
+            type Output = FixedVec2;
+
+            #[inline]
+            fn $method(self, rhs: FixedVec2) -> FixedVec2 {
+                $op::$method(FixedVec2::splat(self), rhs)
+            }
+        }
+
+        impl $op<FixedVec2> for f32 {
+            type Output = FixedVec2;
+
+            #[inline]
+            fn $method(self, rhs: FixedVec2) -> FixedVec2 {
+                $op::$method(Fixed::from_f32(self), rhs)
+            }
+        }
+
+        impl $assign for FixedVec2 {
+            #[inline]
+            fn $assign_method(&mut self, rhs: FixedVec2) {
+                *self = $op::$method(*self, rhs);
+            }
+        }
+
+        impl $assign<Fixed> for FixedVec2 {
+            #[inline]
+            fn $assign_method(&mut self, rhs: Fixed) {
+                *self = $op::$method(*self, rhs);
+            }
+        }
+    )*};
+}
+
+impl_vector_ops!(
+    Add add AddAssign add_assign,
+    Sub sub SubAssign sub_assign,
+    Mul mul MulAssign mul_assign,
+    Div div DivAssign div_assign
+);
+
+/// [`Vector`] from its components, literals included.
+#[inline]
+pub fn vec2(x: impl Into<Real>, y: impl Into<Real>) -> Vector {
+    Vector::new(x.into(), y.into())
+}
+
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn real(value: f32) -> Real {
+    Fixed::from_f32(value)
+}
+
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn real(value: f32) -> Real {
+    value
+}
+
+#[cfg(feature = "deterministic")]
+#[inline]
+pub fn to_f32(value: Real) -> f32 {
+    value.to_f32()
+}
+
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn to_f32(value: Real) -> f32 {
+    value
+}
+
+#[inline]
+pub fn vector(value: Vec2) -> Vector {
+    vec2(real(value.x), real(value.y))
+}
+
+#[inline]
+pub fn to_vec2(value: Vector) -> Vec2 {
+    Vec2::new(to_f32(value.x), to_f32(value.y))
+}
+
+#[cfg(feature = "deterministic")]
+pub fn sin_cos(angle: f32) -> (f32, f32) {
+    let (sin, cos) = Fixed::from_f32(angle).sin_cos();
+
+    (sin.to_f32(), cos.to_f32())
+}
+
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn sin_cos(angle: f32) -> (f32, f32) {
+    angle.sin_cos()
+}
+
+#[cfg(feature = "deterministic")]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    Fixed::from_f32(y).atan2(Fixed::from_f32(x)).to_f32()
+}
+
+#[cfg(not(feature = "deterministic"))]
+#[inline]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    y.atan2(x)
+}
+
+/// Unit vector pointing at `angle` radians.
+#[inline]
+pub fn from_angle(angle: f32) -> Vec2 {
+    let (sin, cos) = sin_cos(angle);
+
+    Vec2::new(cos, sin)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    /// Angles from -4 pi to 4 pi, covering every range reduction.
+    fn angles() -> impl Iterator<Item = f32> {
+        (-4000..=4000).map(|i| i as f32 * PI / 1000.0)
+    }
+
+    #[test]
+    fn sin_and_cos_stay_within_bounds() {
+        for angle in angles() {
+            let fixed = Fixed::from_f32(angle);
+            // The angle itself is rounded to the grid first.
+            let angle = fixed.to_f32();
+
+            assert!(
+                (fixed.sin().to_f32() - angle.sin()).abs() < 5e-5,
+                "sin({angle})"
+            );
+            assert!(
+                (fixed.cos().to_f32() - angle.cos()).abs() < 5e-5,
+                "cos({angle})"
+            );
+        }
+    }
+
+    #[test]
+    fn atan2_stays_within_bounds() {
+        for angle in angles() {
+            for radius in [0.01, 1.0, 300.0] {
+                let (y, x) = (angle.sin() * radius, angle.cos() * radius);
+                let (fixed_y, fixed_x) = (Fixed::from_f32(y), Fixed::from_f32(x));
+                let expected = fixed_y.to_f32().atan2(fixed_x.to_f32());
+                let actual = fixed_y.atan2(fixed_x).to_f32();
+
+                // Both ends of the circle are the same angle.
+                let error = (actual - expected).abs();
+                let error = error.min(2.0 * PI - error);
+
+                assert!(error < 2e-3, "atan2({y}, {x}) = {actual}, not {expected}");
+            }
+        }
+
+        assert_eq!(Fixed::ZERO.atan2(Fixed::ZERO), Fixed::ZERO);
+    }
+
+    #[test]
+    fn arithmetic_saturates() {
+        let big = Fixed::from_f32(1e10);
+
+        assert_eq!(big * big, Fixed::MAX);
+        assert_eq!(big * -big, Fixed::MIN);
+        assert_eq!(big / Fixed(1), Fixed::MAX);
+        assert_eq!(Fixed::MAX + big, Fixed::MAX);
+        assert_eq!(Fixed::MIN - big, Fixed::MIN);
+        assert_eq!(big / Fixed::ZERO, Fixed::INFINITY);
+        assert_eq!(-big / Fixed::ZERO, Fixed::NEG_INFINITY);
+        assert_eq!(Fixed::ZERO / Fixed::ZERO, Fixed::ZERO);
+    }
+
+    #[test]
+    fn arithmetic_rounds_to_the_grid() {
+        let half = Fixed::from_f32(0.5);
+        let three = Fixed::from_f32(3.0);
+
+        assert_eq!(half * three, Fixed::from_f32(1.5));
+        assert_eq!(three / half, Fixed::from_f32(6.0));
+        assert_eq!(Fixed::from_f32(1.0 / 65536.0), Fixed(1));
+        assert_eq!(Fixed(1).to_f32(), 1.0 / 65536.0);
+        assert_eq!(Fixed::ONE / three * three, Fixed::from_f32(1.0) - Fixed(1));
+    }
+
+    #[test]
+    fn rounding_matches_f32() {
+        let value = Fixed::from_f32(-2.75);
+
+        assert_eq!(value.floor(), -3.0);
+        assert_eq!(value.fract(), -0.75);
+        assert_eq!(value.rem_euclid(Fixed::ONE), 0.25);
+        assert_eq!(value.signum(), -1.0);
+        assert_eq!(Fixed::ZERO.signum(), 1.0);
+        assert_eq!(Fixed::from_f32(6.25).sqrt(), 2.5);
+        assert_eq!(value.sqrt(), Fixed::ZERO);
+    }
+
+    #[test]
+    fn mixes_with_f32() {
+        let value = Fixed::from_f32(1.5);
+
+        assert_eq!(value * 2.0, 3.0);
+        assert_eq!(2.0 - value, 0.5);
+        assert!(value > 1.0 && 2.0 > value);
+        assert!((0.0..=1.5).contains(&value));
+    }
+
+    #[test]
+    fn vectors_match_vec2() {
+        let a = Vec2::new(3.0, -4.0);
+        let b = Vec2::new(-1.5, 2.0);
+        let (fixed_a, fixed_b) = (FixedVec2::from_vec2(a), FixedVec2::from_vec2(b));
+
+        assert_eq!(fixed_a.dot(fixed_b), a.dot(b));
+        assert_eq!(fixed_a.perp_dot(fixed_b), a.perp_dot(b));
+        assert_eq!(fixed_a.length(), a.length());
+        assert!(fixed_a
+            .normalize_or_zero()
+            .to_vec2()
+            .abs_diff_eq(a.normalize_or_zero(), 1e-4));
+        assert_eq!((fixed_a * fixed_b).to_vec2(), a * b);
+        assert_eq!((fixed_a.min(fixed_b) - 1.0).to_vec2(), a.min(b) - 1.0);
+        assert_eq!(fixed_a.rotate(fixed_b).to_vec2(), a.rotate(b));
+        assert_eq!(
+            FixedVec2::new(Fixed(1), Fixed(0)).normalize_or_zero(),
+            FixedVec2::X
+        );
+        assert_eq!(FixedVec2::ZERO.normalize_or_zero(), FixedVec2::ZERO);
+    }
+}
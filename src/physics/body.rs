@@ -0,0 +1,96 @@
+use breakout_macros::Component;
+use ggez::glam::Vec2;
+
+use crate::geometry::Shape;
+use crate::geometry::Transform2D;
+use crate::math::Real;
+use crate::math::Vector;
+
+#[derive(Debug, Clone, Component)]
+pub struct Position(pub Vec2);
+
+#[derive(Debug, Clone, Component)]
+pub struct Velocity(pub Vec2);
+
+/// Angle in radians the [`Collider`] is turned by, around the entity [`Position`].
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Rotation(pub f32);
+
+/// Radians per second kinematic bodies turn by.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct AngularVelocity(pub f32);
+
+/// How the physics step moves a body, entities without one are static.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Component)]
+pub enum RigidBody {
+    /// Never moves.
+    #[default]
+    Static,
+    /// Moved by its velocity only, pushing dynamic bodies out of the way.
+    Kinematic,
+    /// Moved by its velocity and bounced off the other bodies.
+    Dynamic,
+}
+
+/// How much of the velocity is kept when bouncing, 1 being a perfect bounce.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Restitution(pub f32);
+
+/// Mass of a dynamic body when exchanging momentum with another dynamic body,
+/// bodies without one weigh 1.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Mass(pub f32);
+
+/// Shape used by the physics step, relative to the entity [`Position`].
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Collider {
+    pub shape: Shape,
+    /// Layers the collider belongs to.
+    pub layer: u32,
+    /// Layers the collider collides with.
+    pub mask: u32,
+    /// Sensors report overlaps without pushing anything.
+    pub sensor: bool,
+}
+
+impl Collider {
+    pub fn new(shape: impl Into<Shape>) -> Collider {
+        Collider {
+            shape: shape.into(),
+            layer: 1,
+            mask: u32::MAX,
+            sensor: false,
+        }
+    }
+
+    #[inline]
+    pub fn with_layer(mut self, layer: u32) -> Collider {
+        self.layer = layer;
+        self
+    }
+
+    #[inline]
+    pub fn with_mask(mut self, mask: u32) -> Collider {
+        self.mask = mask;
+        self
+    }
+
+    #[inline]
+    pub fn as_sensor(mut self) -> Collider {
+        self.sensor = true;
+        self
+    }
+
+    /// The shape in world space for a body at `position`, turned by `rotation`.
+    #[inline]
+    pub fn place(&self, position: impl Into<Vector>, rotation: impl Into<Real>) -> Shape {
+        self.shape
+            .transform(&Transform2D::new(position, rotation, Vector::ONE))
+    }
+
+    /// Both colliders have to accept each other's layer to collide.
+    #[inline]
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        self.mask & other.layer != 0 && other.mask & self.layer != 0
+    }
+}
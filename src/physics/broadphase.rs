@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::ecs::entity::Entity;
+use crate::geometry::Aabb;
+use crate::math;
+use crate::math::Vector;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct CellRange {
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+impl CellRange {
+    fn cells(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.min.0..=self.max.0).flat_map(move |x| (self.min.1..=self.max.1).map(move |y| (x, y)))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Proxy {
+    bounds: Aabb,
+    cells: CellRange,
+}
+
+/// Uniform grid bucketing entities by the cells their bounds overlap, so only
+/// nearby entities have to be tested against each other.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+    proxies: HashMap<Entity, Proxy>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> SpatialGrid {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+            proxies: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.proxies.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.proxies.is_empty()
+    }
+
+    pub fn contains(&self, entity: &Entity) -> bool {
+        self.proxies.contains_key(entity)
+    }
+
+    pub fn bounds(&self, entity: &Entity) -> Option<Aabb> {
+        self.proxies.get(entity).map(|proxy| proxy.bounds)
+    }
+
+    /// Inserts `entity`, or moves it if it is already in the grid. Cells are
+    /// only touched when the bounds cross into other cells.
+    pub fn insert(&mut self, entity: Entity, bounds: Aabb) {
+        let cells = self.cell_range(&bounds);
+
+        if let Some(proxy) = self.proxies.get_mut(&entity) {
+            proxy.bounds = bounds;
+
+            if proxy.cells == cells {
+                return;
+            }
+
+            let previous = proxy.cells;
+            proxy.cells = cells;
+
+            self.unlink(&entity, previous);
+        } else {
+            self.proxies.insert(entity.clone(), Proxy { bounds, cells });
+        }
+
+        for cell in cells.cells() {
+            self.cells.entry(cell).or_default().push(entity.clone());
+        }
+    }
+
+    pub fn remove(&mut self, entity: &Entity) -> Option<Aabb> {
+        let proxy = self.proxies.remove(entity)?;
+
+        self.unlink(entity, proxy.cells);
+
+        Some(proxy.bounds)
+    }
+
+    /// Removes every entity `keep` returns false for.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Entity) -> bool) {
+        let removed: Vec<Entity> = self
+            .proxies
+            .keys()
+            .filter(|entity| !keep(entity))
+            .cloned()
+            .collect();
+
+        for entity in removed.iter() {
+            self.remove(entity);
+        }
+    }
+
+    /// Entities whose bounds overlap `region`.
+    pub fn query_region(&self, region: &Aabb) -> Vec<Entity> {
+        let mut seen = HashSet::new();
+        let mut found = Vec::new();
+
+        for cell in self.cell_range(region).cells() {
+            let Some(entities) = self.cells.get(&cell) else {
+                continue;
+            };
+
+            for entity in entities.iter() {
+                if !seen.insert(entity) {
+                    continue;
+                }
+
+                if self.proxies[entity].bounds.intersection(region).is_some() {
+                    found.push(entity.clone());
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Entities whose bounds contain `point`.
+    pub fn query_point(&self, point: Vector) -> Vec<Entity> {
+        let cell = self.cell(point);
+
+        match self.cells.get(&cell) {
+            Some(entities) => entities
+                .iter()
+                .filter(|entity| self.proxies[*entity].bounds.contains(point))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Every pair of entities whose bounds overlap, each pair reported once.
+    pub fn pairs(&self) -> Vec<(Entity, Entity)> {
+        let mut seen = HashSet::new();
+        let mut pairs = Vec::new();
+
+        for entities in self.cells.values() {
+            for (i, a) in entities.iter().enumerate() {
+                for b in entities[i + 1..].iter() {
+                    let pair = if a < b { (a, b) } else { (b, a) };
+
+                    if !seen.insert(pair) {
+                        continue;
+                    }
+
+                    if self.proxies[a]
+                        .bounds
+                        .intersection(&self.proxies[b].bounds)
+                        .is_some()
+                    {
+                        pairs.push((pair.0.clone(), pair.1.clone()));
+                    }
+                }
+            }
+        }
+
+        // Cells are not visited in any particular order.
+        pairs.sort();
+
+        pairs
+    }
+
+    fn unlink(&mut self, entity: &Entity, cells: CellRange) {
+        for cell in cells.cells() {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|other| other != entity);
+
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn cell(&self, point: Vector) -> (i32, i32) {
+        (
+            math::to_f32((point.x / self.cell_size).floor()) as i32,
+            math::to_f32((point.y / self.cell_size).floor()) as i32,
+        )
+    }
+
+    #[inline]
+    fn cell_range(&self, bounds: &Aabb) -> CellRange {
+        CellRange {
+            min: self.cell(bounds.min),
+            max: self.cell(bounds.max),
+        }
+    }
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid::new(128.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::entity::Entities;
+    use crate::math::vec2;
+
+    use super::*;
+
+    fn entities(count: usize) -> Vec<Entity> {
+        Entities::default().alloc_batch(count)
+    }
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn insert_moves_entities_between_cells() {
+        let mut grid = SpatialGrid::new(10.0);
+        let entity = entities(1).remove(0);
+
+        grid.insert(entity.clone(), Aabb::new(vec2(1.0, 1.0), 2.0, 2.0));
+        grid.insert(entity.clone(), Aabb::new(vec2(31.0, 1.0), 2.0, 2.0));
+
+        assert_eq!(grid.len(), 1);
+        assert!(grid.query_point(vec2(2.0, 2.0)).is_empty());
+        assert_eq!(grid.query_point(vec2(32.0, 2.0)), vec![entity.clone()]);
+        assert_eq!(grid.cells.len(), 1);
+    }
+
+    #[test]
+    fn remove_and_retain_unlink_every_cell() {
+        let mut grid = SpatialGrid::new(10.0);
+        let entities = entities(3);
+
+        for (index, entity) in entities.iter().enumerate() {
+            let position = vec2(index as f32 * 5.0, 0.0);
+
+            grid.insert(entity.clone(), Aabb::new(position, 15.0, 15.0));
+        }
+
+        let bounds = grid.bounds(&entities[0]);
+
+        assert_eq!(grid.remove(&entities[0]), bounds);
+        assert_eq!(grid.remove(&entities[0]), None);
+
+        grid.retain(|entity| *entity != entities[1]);
+
+        assert_eq!(grid.len(), 1);
+        assert!(grid.contains(&entities[2]));
+        assert!(grid
+            .cells
+            .values()
+            .all(|cell| cell.as_slice() == [entities[2].clone()]));
+    }
+
+    #[test]
+    fn bodies_spanning_cells_are_found_from_each() {
+        let mut grid = SpatialGrid::new(10.0);
+        let entity = entities(1).remove(0);
+
+        grid.insert(entity.clone(), Aabb::new(vec2(-5.0, -5.0), 30.0, 10.0));
+
+        assert_eq!(grid.cells.len(), 8);
+
+        for x in [-4.0, 4.0, 14.0, 24.0] {
+            assert_eq!(grid.query_point(vec2(x, 0.0)), vec![entity.clone()]);
+        }
+    }
+
+    #[test]
+    fn query_region_reports_overlapping_bounds_once() {
+        let mut grid = SpatialGrid::new(10.0);
+        let entities = entities(3);
+
+        grid.insert(entities[0].clone(), Aabb::new(vec2(0.0, 0.0), 25.0, 25.0));
+        // Shares cells with the region without overlapping it.
+        grid.insert(entities[1].clone(), Aabb::new(vec2(21.0, 21.0), 2.0, 2.0));
+        grid.insert(entities[2].clone(), Aabb::new(vec2(50.0, 50.0), 5.0, 5.0));
+
+        let region = Aabb::new(vec2(5.0, 5.0), 15.0, 15.0);
+
+        assert_eq!(grid.query_region(&region), vec![entities[0].clone()]);
+        assert_eq!(
+            sorted(grid.query_region(&Aabb::new(Vector::ZERO, 60.0, 60.0))),
+            entities
+        );
+    }
+
+    #[test]
+    fn pairs_are_reported_once() {
+        let mut grid = SpatialGrid::new(10.0);
+        let entities = entities(3);
+
+        // Both span the same four cells.
+        grid.insert(entities[0].clone(), Aabb::new(vec2(5.0, 5.0), 10.0, 10.0));
+        grid.insert(entities[1].clone(), Aabb::new(vec2(6.0, 6.0), 10.0, 10.0));
+        grid.insert(entities[2].clone(), Aabb::new(vec2(40.0, 40.0), 10.0, 10.0));
+
+        assert_eq!(
+            grid.pairs(),
+            vec![(entities[0].clone(), entities[1].clone())]
+        );
+    }
+}
@@ -1,32 +1,909 @@
-use ggez::glam::vec2;
-use ggez::glam::Vec2;
-
-pub fn aabb(
-    pos_a: Vec2,
-    dim_a_w: f32,
-    dim_a_h: f32,
-    vel_a: Vec2,
-    pos_b: Vec2,
-    dim_b_w: f32,
-    dim_b_h: f32,
-    intersection_w: f32,
-    intersection_h: f32,
-) -> (Vec2, Vec2) {
-    let center_a = vec2(pos_a.x + dim_a_w * 0.5, pos_a.y + dim_a_h * 0.5);
-    let center_b = vec2(pos_b.x + dim_b_w * 0.5, pos_b.y + dim_b_h * 0.5);
-
-    let to_signum = (center_b - center_a).signum();
-
-    let mut pos = vec2(pos_a.x, pos_a.y);
-    let mut vel = vec2(vel_a.x, vel_a.y);
-
-    if intersection_w > intersection_h {
-        pos.y -= to_signum.y * intersection_h;
-        vel.y = -to_signum.y * vel.y.abs();
+use crate::geometry::Aabb;
+use crate::geometry::Circle;
+use crate::geometry::Polygon;
+use crate::geometry::Shape;
+use crate::math::vec2;
+use crate::math::Real;
+use crate::math::Scalar;
+use crate::math::Vector;
+
+/// How two overlapping shapes touch, seen from the first one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// Direction the first shape has to move to get out of the second one.
+    pub normal: Vector,
+    /// How far the shapes overlap along the normal.
+    pub depth: Real,
+    /// Where the shapes touch.
+    pub point: Vector,
+}
+
+impl Contact {
+    /// The same contact seen from the second shape.
+    #[inline]
+    pub fn flip(self) -> Contact {
+        Contact {
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+/// First contact of a shape moving against a static one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Impact {
+    /// Fraction of the displacement travelled before touching, from 0 to 1.
+    pub time: Real,
+    /// Contact at that time, only deep when the shapes were already overlapping.
+    pub contact: Contact,
+}
+
+pub fn collide(a: &Shape, b: &Shape) -> Option<Contact> {
+    match (a, b) {
+        (Shape::Aabb(a), Shape::Aabb(b)) => aabb_aabb(a, b),
+        (Shape::Circle(a), Shape::Aabb(b)) => circle_aabb(a, b),
+        (Shape::Aabb(a), Shape::Circle(b)) => circle_aabb(b, a).map(Contact::flip),
+        (Shape::Circle(a), Shape::Circle(b)) => circle_circle(a, b),
+        (Shape::Polygon(a), Shape::Polygon(b)) => polygon_polygon(a, b),
+        (Shape::Aabb(a), Shape::Polygon(b)) => polygon_polygon(&(*a).into(), b),
+        (Shape::Polygon(a), Shape::Aabb(b)) => polygon_polygon(a, &(*b).into()),
+        (Shape::Circle(a), Shape::Polygon(b)) => circle_polygon(a, b),
+        (Shape::Polygon(a), Shape::Circle(b)) => circle_polygon(b, a).map(Contact::flip),
+    }
+}
+
+/// Finds when `a` first touches `b` while moving by `displacement`.
+pub fn sweep(a: &Shape, displacement: Vector, b: &Shape) -> Option<Impact> {
+    match (a, b) {
+        (Shape::Aabb(a), Shape::Aabb(b)) => swept_aabb(a, displacement, b),
+        (Shape::Circle(a), Shape::Aabb(b)) => swept_circle(a, displacement, b),
+        (Shape::Aabb(a), Shape::Circle(b)) => {
+            swept_circle(b, -displacement, a).map(|impact| mirror(impact, displacement))
+        }
+        (Shape::Circle(a), Shape::Circle(b)) => swept_circle_circle(a, displacement, b),
+        (Shape::Polygon(a), Shape::Polygon(b)) => swept_polygon(a, displacement, b),
+        (Shape::Aabb(a), Shape::Polygon(b)) => swept_polygon(&(*a).into(), displacement, b),
+        (Shape::Polygon(a), Shape::Aabb(b)) => swept_polygon(a, displacement, &(*b).into()),
+        (Shape::Circle(a), Shape::Polygon(b)) => swept_circle_polygon(a, displacement, b),
+        (Shape::Polygon(a), Shape::Circle(b)) => {
+            swept_circle_polygon(b, -displacement, a).map(|impact| mirror(impact, displacement))
+        }
+    }
+}
+
+/// Reflects `vel` around `normal`, keeping its length.
+pub fn reflect(vel: Vector, normal: Vector) -> Vector {
+    vel - 2.0 * vel.dot(normal) * normal
+}
+
+/// Bounces `vel` off a surface facing `normal`, keeping `restitution` of the
+/// speed going into it. Velocities already leaving the surface are untouched.
+pub fn bounce(vel: Vector, normal: Vector, restitution: Real) -> Vector {
+    let approaching = vel.dot(normal);
+
+    if approaching >= 0.0 {
+        return vel;
+    }
+
+    vel - (1.0 + restitution) * approaching * normal
+}
+
+/// How far a contact point can be from another contact surface and still be
+/// considered on it.
+const SEAM_TOLERANCE: f32 = 0.01;
+
+/// Contacts touching at the same time, on a seam between two shapes lined up
+/// with each other, take the normal of the surface they lie on. That way a
+/// corner hidden by its neighbour acts like the rest of the flat surface.
+pub fn merge_seams(contacts: &mut [Contact]) {
+    for i in 0..contacts.len() {
+        let contact = contacts[i];
+
+        let surface = contacts.iter().find(|surface| {
+            surface.normal != contact.normal
+                && surface.normal.dot(contact.normal) > 0.0
+                && (contact.point - surface.point).dot(surface.normal).abs() < SEAM_TOLERANCE
+        });
+
+        if let Some(surface) = surface {
+            contacts[i].normal = surface.normal;
+        }
+    }
+}
+
+/// Single normal to respond to several contacts with, the average of their
+/// different normals.
+pub fn combined_normal(contacts: &[Contact]) -> Vector {
+    let mut normals: Vec<Vector> = Vec::new();
+
+    for contact in contacts.iter() {
+        if !normals
+            .iter()
+            .any(|normal| normal.abs_diff_eq(contact.normal, 1e-4))
+        {
+            normals.push(contact.normal);
+        }
+    }
+
+    normals.iter().sum::<Vector>().normalize_or_zero()
+}
+
+/// Exchanges momentum between two bodies touching along `normal`, seen from
+/// `a`, keeping `restitution` of their relative speed. Bodies already moving
+/// apart are untouched.
+pub fn impulse(
+    vel_a: Vector,
+    mass_a: Real,
+    vel_b: Vector,
+    mass_b: Real,
+    normal: Vector,
+    restitution: Real,
+) -> (Vector, Vector) {
+    let approaching = (vel_a - vel_b).dot(normal);
+
+    if approaching >= 0.0 {
+        return (vel_a, vel_b);
+    }
+
+    let impulse = -(1.0 + restitution) * approaching / (1.0 / mass_a + 1.0 / mass_b);
+
+    (
+        vel_a + normal * impulse / mass_a,
+        vel_b - normal * impulse / mass_b,
+    )
+}
+
+pub fn aabb_aabb(a: &Aabb, b: &Aabb) -> Option<Contact> {
+    let intersection = a.intersection(b)?;
+    let overlap = intersection.size();
+
+    let to_signum = (a.center() - b.center()).signum();
+
+    let (normal, depth) = if overlap.x > overlap.y {
+        (vec2(0.0, to_signum.y), overlap.y)
+    } else {
+        (vec2(to_signum.x, 0.0), overlap.x)
+    };
+
+    Some(Contact {
+        normal,
+        depth,
+        point: intersection.center(),
+    })
+}
+
+pub fn circle_aabb(a: &Circle, b: &Aabb) -> Option<Contact> {
+    let closest = b.closest_point(a.center);
+    let offset = a.center - closest;
+    let distance_squared = offset.length_squared();
+
+    if distance_squared > a.radius * a.radius {
+        return None;
+    }
+
+    if distance_squared > 0.0 {
+        let distance = distance_squared.sqrt();
+
+        return Some(Contact {
+            normal: offset / distance,
+            depth: a.radius - distance,
+            point: closest,
+        });
+    }
+
+    // The center is inside the rectangle, push it out through the nearest side.
+    let to_min = a.center - b.min;
+    let to_max = b.max - a.center;
+
+    let sides = [
+        (vec2(-1.0, 0.0), to_min.x),
+        (vec2(1.0, 0.0), to_max.x),
+        (vec2(0.0, -1.0), to_min.y),
+        (vec2(0.0, 1.0), to_max.y),
+    ];
+
+    sides
+        .into_iter()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(normal, distance)| Contact {
+            normal,
+            depth: distance + a.radius,
+            point: a.center + normal * distance,
+        })
+}
+
+pub fn circle_circle(a: &Circle, b: &Circle) -> Option<Contact> {
+    let offset = a.center - b.center;
+    let radii = a.radius + b.radius;
+
+    if offset.length_squared() > radii * radii {
+        return None;
+    }
+
+    let distance = offset.length();
+
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        vec2(0.0, -1.0)
+    };
+
+    Some(Contact {
+        normal,
+        depth: radii - distance,
+        point: b.center + normal * b.radius,
+    })
+}
+
+/// Separating axis test, the shapes overlap unless one of the edge normals
+/// separates them.
+pub fn polygon_polygon(a: &Polygon, b: &Polygon) -> Option<Contact> {
+    let mut best: Option<(Vector, Real, bool)> = None;
+
+    // Edges of `b` come first, so a face against a parallel face is resolved
+    // against the face being hit.
+    for (axis, from_a) in b
+        .normals()
+        .map(|axis| (axis, false))
+        .chain(a.normals().map(|axis| (axis, true)))
+    {
+        let overlap = overlap(a.project(axis), b.project(axis))?;
+
+        if best.is_none_or(|(_, depth, _)| overlap < depth) {
+            best = Some((axis, overlap, from_a));
+        }
+    }
+
+    let (axis, depth, from_a) = best?;
+    let normal = facing(axis, a.center() - b.center());
+
+    // The deepest vertex of whichever polygon did not provide the edge.
+    let point = if from_a {
+        b.support(normal)
+    } else {
+        a.support(-normal)
+    };
+
+    Some(Contact {
+        normal,
+        depth,
+        point,
+    })
+}
+
+pub fn circle_polygon(a: &Circle, b: &Polygon) -> Option<Contact> {
+    let closest = b.vertices().iter().copied().min_by(|x, y| {
+        x.distance_squared(a.center)
+            .total_cmp(&y.distance_squared(a.center))
+    })?;
+
+    let vertex_axis = (a.center - closest).normalize_or_zero();
+
+    let mut best: Option<(Vector, Real)> = None;
+
+    for axis in b.normals().chain([vertex_axis]) {
+        if axis == Vector::ZERO {
+            continue;
+        }
+
+        let center = a.center.dot(axis);
+        let overlap = overlap((center - a.radius, center + a.radius), b.project(axis))?;
+
+        if best.is_none_or(|(_, depth)| overlap < depth) {
+            best = Some((axis, overlap));
+        }
+    }
+
+    let (axis, depth) = best?;
+    let normal = facing(axis, a.center - b.center());
+
+    Some(Contact {
+        normal,
+        depth,
+        point: a.center - normal * (a.radius - depth),
+    })
+}
+
+pub fn swept_aabb(a: &Aabb, displacement: Vector, b: &Aabb) -> Option<Impact> {
+    if let Some(contact) = aabb_aabb(a, b) {
+        return approaching(contact, displacement);
+    }
+
+    // Sweeping a box against a box is the same as casting its corner against
+    // the other box grown by its size.
+    let bounds = Aabb {
+        min: b.min - a.size(),
+        max: b.max,
+    };
+
+    let (time, normal) = ray_aabb(a.min, displacement, &bounds)?;
+
+    Some(Impact {
+        time,
+        contact: Contact {
+            normal,
+            depth: Real::ZERO,
+            point: b.closest_point(a.center() + displacement * time),
+        },
+    })
+}
+
+pub fn swept_circle(a: &Circle, displacement: Vector, b: &Aabb) -> Option<Impact> {
+    if let Some(contact) = circle_aabb(a, b) {
+        return approaching(contact, displacement);
+    }
+
+    // Cast the center against the rectangle grown by the radius, then round
+    // its corners when the hit lands outside of both faces.
+    let (time, normal) = ray_aabb(a.center, displacement, &b.expand(Vector::splat(a.radius)))?;
+
+    let hit = a.center + displacement * time;
+
+    if (b.min.x..=b.max.x).contains(&hit.x) || (b.min.y..=b.max.y).contains(&hit.y) {
+        return Some(Impact {
+            time,
+            contact: Contact {
+                normal,
+                depth: Real::ZERO,
+                point: b.closest_point(hit),
+            },
+        });
+    }
+
+    let corner = b.closest_point(hit);
+
+    swept_circle_circle(a, displacement, &Circle::new(corner, 0.0))
+}
+
+pub fn swept_circle_circle(a: &Circle, displacement: Vector, b: &Circle) -> Option<Impact> {
+    if let Some(contact) = circle_circle(a, b) {
+        return approaching(contact, displacement);
+    }
+
+    let (time, normal) = ray_circle(a.center, displacement, b.center, a.radius + b.radius)?;
+
+    Some(Impact {
+        time,
+        contact: Contact {
+            normal,
+            depth: Real::ZERO,
+            point: b.center + normal * b.radius,
+        },
+    })
+}
+
+/// Separating axis test over time, the polygons touch once every axis
+/// projection has started overlapping, as long as none has stopped.
+pub fn swept_polygon(a: &Polygon, displacement: Vector, b: &Polygon) -> Option<Impact> {
+    if let Some(contact) = polygon_polygon(a, b) {
+        return approaching(contact, displacement);
+    }
+
+    let mut entry = Real::NEG_INFINITY;
+    let mut exit = Real::INFINITY;
+    let mut normal = Vector::ZERO;
+    let mut from_a = false;
+
+    for (axis, is_a) in b
+        .normals()
+        .map(|axis| (axis, false))
+        .chain(a.normals().map(|axis| (axis, true)))
+    {
+        let (a_min, a_max) = a.project(axis);
+        let (b_min, b_max) = b.project(axis);
+        let speed = displacement.dot(axis);
+
+        if speed == 0.0 {
+            if a_max < b_min || a_min > b_max {
+                return None;
+            }
+
+            continue;
+        }
+
+        let near = (b_min - a_max) / speed;
+        let far = (b_max - a_min) / speed;
+
+        let (near, far) = (near.min(far), near.max(far));
+
+        if near > entry {
+            entry = near;
+            normal = -axis * speed.signum();
+            from_a = is_a;
+        }
+
+        exit = exit.min(far);
+    }
+
+    if entry > exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+
+    let a = a.translate(displacement * entry);
+
+    let point = if from_a {
+        b.support(normal)
     } else {
-        pos.x -= to_signum.x * intersection_w;
-        vel.x = -to_signum.x * vel.x.abs();
+        a.support(-normal)
+    };
+
+    Some(Impact {
+        time: entry,
+        contact: Contact {
+            normal,
+            depth: Real::ZERO,
+            point,
+        },
+    })
+}
+
+/// Casts the center against the polygon grown by the radius, which is every
+/// edge pushed out along its normal and a circle around every vertex.
+pub fn swept_circle_polygon(a: &Circle, displacement: Vector, b: &Polygon) -> Option<Impact> {
+    if let Some(contact) = circle_polygon(a, b) {
+        return approaching(contact, displacement);
+    }
+
+    let mut earliest: Option<Impact> = None;
+
+    for ((start, end), normal) in b.edges().zip(b.normals()) {
+        let speed = displacement.dot(normal);
+
+        if speed >= 0.0 {
+            continue;
+        }
+
+        let time = (start.dot(normal) + a.radius - a.center.dot(normal)) / speed;
+
+        if !(0.0..=1.0).contains(&time) {
+            continue;
+        }
+
+        let point = a.center + displacement * time - normal * a.radius;
+        let along = (point - start).dot(end - start);
+
+        if along < 0.0 || along > (end - start).length_squared() {
+            continue;
+        }
+
+        if earliest.is_none_or(|impact| time < impact.time) {
+            earliest = Some(Impact {
+                time,
+                contact: Contact {
+                    normal,
+                    depth: Real::ZERO,
+                    point,
+                },
+            });
+        }
     }
 
-    (pos, vel)
+    for vertex in b.vertices() {
+        let Some(impact) = swept_circle_circle(a, displacement, &Circle::new(*vertex, 0.0)) else {
+            continue;
+        };
+
+        if earliest.is_none_or(|earliest| impact.time < earliest.time) {
+            earliest = Some(impact);
+        }
+    }
+
+    earliest
+}
+
+/// Shapes already overlapping only collide when moving further into each other.
+fn approaching(contact: Contact, displacement: Vector) -> Option<Impact> {
+    if displacement.dot(contact.normal) < 0.0 {
+        return Some(Impact {
+            time: Real::ZERO,
+            contact,
+        });
+    }
+
+    None
+}
+
+/// An impact of `b` moving by `-displacement` against `a`, seen from `a` moving
+/// by `displacement` against `b`.
+fn mirror(impact: Impact, displacement: Vector) -> Impact {
+    Impact {
+        time: impact.time,
+        contact: Contact {
+            point: impact.contact.point + displacement * impact.time,
+            ..impact.contact.flip()
+        },
+    }
+}
+
+/// How much two projections overlap, if they do.
+fn overlap(a: (Real, Real), b: (Real, Real)) -> Option<Real> {
+    let overlap = a.1.min(b.1) - a.0.max(b.0);
+
+    if overlap < 0.0 {
+        return None;
+    }
+
+    Some(overlap)
+}
+
+/// `axis` flipped to point along `direction`.
+fn facing(axis: Vector, direction: Vector) -> Vector {
+    if axis.dot(direction) < 0.0 {
+        -axis
+    } else {
+        axis
+    }
+}
+
+fn ray_aabb(origin: Vector, displacement: Vector, bounds: &Aabb) -> Option<(Real, Vector)> {
+    let mut entry = Real::NEG_INFINITY;
+    let mut exit = Real::INFINITY;
+    let mut normal = Vector::ZERO;
+
+    for axis in 0..2 {
+        if displacement[axis] == 0.0 {
+            if origin[axis] < bounds.min[axis] || origin[axis] > bounds.max[axis] {
+                return None;
+            }
+
+            continue;
+        }
+
+        let near = (bounds.min[axis] - origin[axis]) / displacement[axis];
+        let far = (bounds.max[axis] - origin[axis]) / displacement[axis];
+
+        let (near, far) = (near.min(far), near.max(far));
+
+        if near > entry {
+            entry = near;
+            normal = Vector::ZERO;
+            normal[axis] = -displacement[axis].signum();
+        }
+
+        exit = exit.min(far);
+    }
+
+    if entry > exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+
+    Some((entry, normal))
+}
+
+fn ray_circle(
+    origin: Vector,
+    displacement: Vector,
+    center: Vector,
+    radius: Real,
+) -> Option<(Real, Vector)> {
+    let offset = origin - center;
+
+    let a = displacement.length_squared();
+    let b = 2.0 * offset.dot(displacement);
+    let c = offset.length_squared() - radius * radius;
+
+    let discriminant = b * b - 4.0 * a * c;
+
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+
+    let time = (-b - discriminant.sqrt()) / (2.0 * a);
+
+    if !(0.0..=1.0).contains(&time) {
+        return None;
+    }
+
+    let normal = (origin + displacement * time - center).normalize_or_zero();
+
+    Some((time, normal))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math;
+
+    use super::*;
+
+    /// 100 wide and 20 high, from the origin.
+    fn brick() -> Aabb {
+        Aabb::new(Vector::ZERO, 100.0, 20.0)
+    }
+
+    #[test]
+    fn circle_aabb_pushes_out_through_the_nearest_face() {
+        let above = circle_aabb(&Circle::new(vec2(50.0, -5.0), 8.0), &brick()).unwrap();
+
+        assert_eq!(above.normal, vec2(0.0, -1.0));
+        assert_eq!(above.depth, 3.0);
+        assert_eq!(above.point, vec2(50.0, 0.0));
+
+        let left = circle_aabb(&Circle::new(vec2(-5.0, 10.0), 8.0), &brick()).unwrap();
+
+        assert_eq!(left.normal, vec2(-1.0, 0.0));
+        assert_eq!(left.depth, 3.0);
+        assert_eq!(left.point, vec2(0.0, 10.0));
+
+        // The center is inside, closest to the bottom face.
+        let inside = circle_aabb(&Circle::new(vec2(50.0, 17.0), 8.0), &brick()).unwrap();
+
+        assert_eq!(inside.normal, vec2(0.0, 1.0));
+        assert_eq!(inside.depth, 11.0);
+        assert_eq!(inside.point, vec2(50.0, 20.0));
+    }
+
+    #[test]
+    fn circle_aabb_pushes_out_of_corners_diagonally() {
+        let corner = circle_aabb(&Circle::new(vec2(103.0, 24.0), 8.0), &brick()).unwrap();
+
+        assert!(
+            corner.normal.abs_diff_eq(vec2(0.6, 0.8), 1e-3),
+            "{}",
+            corner.normal
+        );
+        assert_eq!(corner.depth, 3.0);
+        assert_eq!(corner.point, vec2(100.0, 20.0));
+
+        // Within reach of both faces, but not of the corner itself.
+        assert!(circle_aabb(&Circle::new(vec2(107.0, 27.0), 8.0), &brick()).is_none());
+    }
+
+    #[test]
+    fn swept_circle_stops_touching_the_face() {
+        let ball = Circle::new(vec2(50.0, -20.0), 5.0);
+        let impact = swept_circle(&ball, vec2(0.0, 40.0), &brick()).unwrap();
+
+        assert_eq!(impact.time, 0.375);
+        assert_eq!(impact.contact.normal, vec2(0.0, -1.0));
+        assert_eq!(impact.contact.point, vec2(50.0, 0.0));
+
+        assert!(swept_circle(&ball, vec2(0.0, 10.0), &brick()).is_none());
+        assert!(swept_circle(&ball, vec2(0.0, -40.0), &brick()).is_none());
+    }
+
+    #[test]
+    fn swept_circle_rounds_the_corners() {
+        let ball = Circle::new(vec2(120.0, -20.0), 5.0);
+        let impact = swept_circle(&ball, vec2(-40.0, 40.0), &brick()).unwrap();
+
+        // The center ends up 5 away from the corner along the diagonal.
+        assert!((impact.time - 0.4116).abs() < 1e-3, "{}", impact.time);
+        assert!(
+            impact
+                .contact
+                .normal
+                .abs_diff_eq(vec2(1.0, -1.0).normalize_or_zero(), 1e-3),
+            "{}",
+            impact.contact.normal
+        );
+        assert_eq!(impact.contact.point, vec2(100.0, 0.0));
+    }
+
+    #[test]
+    fn swept_circle_catches_balls_too_fast_to_overlap() {
+        // Far enough in one step to end up past the whole brick.
+        let ball = Circle::new(vec2(50.0, -100.0), 5.0);
+        let displacement = vec2(0.0, 1000.0);
+
+        assert!(circle_aabb(&ball.translate(displacement), &brick()).is_none());
+
+        let impact = swept_circle(&ball, displacement, &brick()).unwrap();
+
+        assert!((impact.time - 0.095).abs() < 1e-4, "{}", impact.time);
+        assert_eq!(impact.contact.normal, vec2(0.0, -1.0));
+    }
+
+    #[test]
+    fn swept_aabb_stops_touching_the_face() {
+        let paddle = Aabb::new(vec2(-30.0, 0.0), 10.0, 10.0);
+        let impact = swept_aabb(&paddle, vec2(40.0, 0.0), &brick()).unwrap();
+
+        assert_eq!(impact.time, 0.5);
+        assert_eq!(impact.contact.normal, vec2(-1.0, 0.0));
+        assert_eq!(impact.contact.point, vec2(0.0, 5.0));
+
+        assert!(swept_aabb(&paddle, vec2(10.0, 0.0), &brick()).is_none());
+    }
+
+    #[test]
+    fn contacts_flip_to_the_other_shape() {
+        let ball = Shape::Circle(Circle::new(vec2(50.0, -5.0), 8.0));
+        let brick = Shape::Aabb(brick());
+
+        let contact = collide(&ball, &brick).unwrap();
+        let flipped = contact.flip();
+
+        assert_eq!(flipped.normal, -contact.normal);
+        assert_eq!(flipped.depth, contact.depth);
+        assert_eq!(flipped.point, contact.point);
+        assert_eq!(flipped.flip(), contact);
+        assert_eq!(collide(&brick, &ball), Some(flipped));
+    }
+
+    #[test]
+    fn sweeps_are_mirrored_for_the_moving_shape() {
+        let ball = Shape::Circle(Circle::new(vec2(50.0, -20.0), 5.0));
+        let brick = Shape::Aabb(brick());
+
+        let impact = sweep(&ball, vec2(0.0, 40.0), &brick).unwrap();
+
+        // The brick moving up into the ball instead.
+        let mirrored = sweep(&brick, vec2(0.0, -40.0), &ball).unwrap();
+
+        assert_eq!(mirrored.time, impact.time);
+        assert_eq!(mirrored.contact.normal, -impact.contact.normal);
+        assert_eq!(mirrored.contact.point, vec2(50.0, -15.0));
+    }
+
+    #[test]
+    fn shapes_move_and_bound_whatever_they_hold() {
+        let brick = Shape::Aabb(brick()).translate(vec2(10.0, 0.0));
+        let ball = Shape::Circle(Circle::new(vec2(50.0, -5.0), 8.0)).translate(vec2(10.0, 0.0));
+
+        assert_eq!(brick.bounds(), Aabb::new(vec2(10.0, 0.0), 100.0, 20.0));
+        assert_eq!(ball.bounds(), Aabb::new(vec2(52.0, -13.0), 16.0, 16.0));
+        assert_eq!(
+            collide(&ball, &brick).map(|contact| contact.point),
+            Some(vec2(60.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn impulse_keeps_momentum_between_unequal_masses() {
+        // A body 3 times heavier running into one standing still.
+        let (mass_a, mass_b) = (math::real(3.0), math::real(1.0));
+        let normal = vec2(-1.0, 0.0);
+
+        for (restitution, speed_a, speed_b) in
+            [(1.0, 5.0, 15.0), (0.5, 6.25, 11.25), (0.0, 7.5, 7.5)]
+        {
+            let (vel_a, vel_b) = impulse(
+                vec2(10.0, 0.0),
+                mass_a,
+                Vector::ZERO,
+                mass_b,
+                normal,
+                math::real(restitution),
+            );
+
+            assert!(vel_a.abs_diff_eq(vec2(speed_a, 0.0), 1e-2), "{vel_a}");
+            assert!(vel_b.abs_diff_eq(vec2(speed_b, 0.0), 1e-2), "{vel_b}");
+            assert!((vel_a * mass_a + vel_b * mass_b).abs_diff_eq(vec2(30.0, 0.0), 1e-2));
+
+            // Restitution is the share of the speed they meet at kept apart.
+            assert!(((vel_b - vel_a).x - restitution * 10.0).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn impulse_leaves_bodies_moving_apart() {
+        let (vel_a, vel_b) = (vec2(-10.0, 0.0), vec2(5.0, 3.0));
+
+        assert_eq!(
+            impulse(
+                vel_a,
+                math::real(3.0),
+                vel_b,
+                math::real(1.0),
+                vec2(-1.0, 0.0),
+                Real::ONE,
+            ),
+            (vel_a, vel_b)
+        );
+    }
+
+    fn square(x: f32, y: f32) -> Polygon {
+        Aabb::new(vec2(x, y), 10.0, 10.0).into()
+    }
+
+    /// Right angle at the origin, its long side facing up and to the right.
+    fn triangle() -> Polygon {
+        Polygon::new(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 10.0)]).unwrap()
+    }
+
+    #[test]
+    fn polygon_polygon_resolves_along_the_shallowest_axis() {
+        let contact = polygon_polygon(&square(0.0, 0.0), &square(8.0, 1.0)).unwrap();
+
+        assert_eq!(contact.normal, vec2(-1.0, 0.0));
+        assert_eq!(contact.depth, 2.0);
+
+        assert!(polygon_polygon(&square(0.0, 0.0), &square(12.0, 1.0)).is_none());
+
+        // The bounds overlap, but the long side separates them.
+        assert!(polygon_polygon(&triangle(), &square(6.0, 6.0)).is_none());
+        assert!(polygon_polygon(&square(6.0, 6.0), &triangle()).is_none());
+    }
+
+    #[test]
+    fn circle_polygon_separates_along_edges_and_vertices() {
+        let contact = circle_polygon(&Circle::new(vec2(6.0, 6.0), 2.0), &triangle()).unwrap();
+
+        assert!(
+            contact
+                .normal
+                .abs_diff_eq(vec2(1.0, 1.0).normalize_or_zero(), 1e-3),
+            "{}",
+            contact.normal
+        );
+        assert!(
+            (contact.depth - (2.0 - 2.0_f32.sqrt())).abs() < 1e-3,
+            "{}",
+            contact.depth
+        );
+
+        assert!(circle_polygon(&Circle::new(vec2(8.0, 8.0), 2.0), &triangle()).is_none());
+
+        // Past the corner, only the axis through the vertex separates them.
+        assert!(circle_polygon(&Circle::new(vec2(12.0, -2.0), 2.0), &triangle()).is_none());
+        assert!(circle_polygon(&Circle::new(vec2(11.0, -1.0), 2.0), &triangle()).is_some());
+    }
+
+    #[test]
+    fn swept_polygon_stops_touching_the_face() {
+        let arrow = Polygon::new(&[vec2(0.0, 0.0), vec2(10.0, 5.0), vec2(0.0, 10.0)]).unwrap();
+        let wall = square(20.0, 0.0);
+
+        let impact = swept_polygon(&arrow, vec2(20.0, 0.0), &wall).unwrap();
+
+        assert_eq!(impact.time, 0.5);
+        assert_eq!(impact.contact.normal, vec2(-1.0, 0.0));
+        assert_eq!(impact.contact.point, vec2(20.0, 5.0));
+
+        assert!(swept_polygon(&arrow, vec2(5.0, 0.0), &wall).is_none());
+        assert!(swept_polygon(&arrow, vec2(20.0, 20.0), &wall).is_none());
+    }
+
+    fn touching(normal: Vector, point: Vector) -> Contact {
+        Contact {
+            normal,
+            depth: Real::ZERO,
+            point,
+        }
+    }
+
+    #[test]
+    fn corner_hits_between_touching_bricks_bounce_once() {
+        // Coming up at the seam between two bricks side by side, hitting the
+        // bottom of the left one and the corner of the right one at once.
+        let mut contacts = [
+            touching(vec2(0.0, 1.0), vec2(48.0, 20.0)),
+            touching(vec2(-0.6, 0.8), vec2(50.0, 20.0)),
+        ];
+
+        merge_seams(&mut contacts);
+
+        assert_eq!(contacts[0].normal, vec2(0.0, 1.0));
+        assert_eq!(contacts[1].normal, vec2(0.0, 1.0));
+
+        let normal = combined_normal(&contacts);
+
+        assert_eq!(normal, vec2(0.0, 1.0));
+        assert_eq!(
+            bounce(vec2(100.0, -200.0), normal, Real::ONE),
+            vec2(100.0, 200.0)
+        );
+    }
+
+    #[test]
+    fn inner_corners_bounce_off_both_surfaces() {
+        // Into the corner of a ceiling and a wall, neither hides the other.
+        let mut contacts = [
+            touching(vec2(0.0, 1.0), vec2(10.0, 0.0)),
+            touching(vec2(1.0, 0.0), vec2(0.0, 10.0)),
+        ];
+
+        merge_seams(&mut contacts);
+
+        let normal = combined_normal(&contacts);
+
+        assert!(
+            normal.abs_diff_eq(vec2(1.0, 1.0).normalize_or_zero(), 1e-3),
+            "{normal}"
+        );
+
+        let vel = bounce(vec2(-100.0, -100.0), normal, Real::ONE);
+
+        assert!(vel.abs_diff_eq(vec2(100.0, 100.0), 1e-1), "{vel}");
+    }
 }
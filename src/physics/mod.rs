@@ -1 +1,6 @@
+pub mod body;
+pub mod broadphase;
 pub mod collision;
+pub mod path;
+pub mod query;
+pub mod step;
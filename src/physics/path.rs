@@ -0,0 +1,175 @@
+use std::f32::consts::TAU;
+
+use breakout_macros::Component;
+use ggez::glam::Vec2;
+
+use crate::math;
+use crate::math::Real;
+use crate::math::Scalar;
+use crate::math::Vector;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Path {
+    /// Back and forth between two points, `period` seconds for a round trip.
+    Linear { from: Vec2, to: Vec2, period: f32 },
+    /// Around `center`, `period` seconds per turn, negative to turn the other way.
+    Circular {
+        center: Vec2,
+        radius: f32,
+        period: f32,
+    },
+    /// Smoothly through every point and back to the first one, spending
+    /// `segment_duration` seconds between two points.
+    Spline {
+        points: Vec<Vec2>,
+        segment_duration: f32,
+    },
+}
+
+impl Path {
+    /// Spline through `points`, `None` with fewer than 2 of them.
+    pub fn spline(points: Vec<Vec2>, segment_duration: f32) -> Option<Path> {
+        if points.len() < 2 {
+            return None;
+        }
+
+        Some(Path::Spline {
+            points,
+            segment_duration,
+        })
+    }
+
+    /// The same path moved by `offset`.
+    pub fn translate(&self, offset: Vec2) -> Path {
+        match self {
+            Path::Linear { from, to, period } => Path::Linear {
+                from: *from + offset,
+                to: *to + offset,
+                period: *period,
+            },
+            Path::Circular {
+                center,
+                radius,
+                period,
+            } => Path::Circular {
+                center: *center + offset,
+                radius: *radius,
+                period: *period,
+            },
+            Path::Spline {
+                points,
+                segment_duration,
+            } => Path::Spline {
+                points: points.iter().map(|point| *point + offset).collect(),
+                segment_duration: *segment_duration,
+            },
+        }
+    }
+}
+
+/// Drives the [`Position`](super::body::Position) of a kinematic body along a
+/// path. The physics step turns it into a velocity, so whatever hits the body
+/// bounces off its motion.
+#[derive(Debug, Clone, Component)]
+pub struct MotionPath {
+    pub path: Path,
+    /// Seconds spent along the path so far.
+    pub elapsed: Real,
+    /// Whether the body was put on the path yet.
+    placed: bool,
+}
+
+impl MotionPath {
+    pub fn new(path: Path) -> MotionPath {
+        MotionPath {
+            path,
+            elapsed: Real::ZERO,
+            placed: false,
+        }
+    }
+
+    pub fn linear(from: Vec2, to: Vec2, period: f32) -> MotionPath {
+        MotionPath::new(Path::Linear { from, to, period })
+    }
+
+    pub fn circular(center: Vec2, radius: f32, period: f32) -> MotionPath {
+        MotionPath::new(Path::Circular {
+            center,
+            radius,
+            period,
+        })
+    }
+
+    /// `None` with fewer than 2 points, see [`Path::spline`].
+    pub fn spline(points: Vec<Vec2>, segment_duration: f32) -> Option<MotionPath> {
+        Path::spline(points, segment_duration).map(MotionPath::new)
+    }
+
+    /// Starts the path `elapsed` seconds in, to offset bodies sharing a path.
+    #[inline]
+    pub fn with_elapsed(mut self, elapsed: Real) -> MotionPath {
+        self.elapsed = elapsed;
+        self
+    }
+
+    /// Where the body has to be put before following the path, only returned
+    /// the first time so it never rushes there from wherever it was spawned.
+    pub fn place(&mut self) -> Option<Vector> {
+        if self.placed {
+            return None;
+        }
+
+        self.placed = true;
+
+        Some(self.position_at(self.elapsed))
+    }
+
+    /// Moves `dt` seconds along the path and returns where the body should be.
+    pub fn advance(&mut self, dt: Real) -> Vector {
+        self.elapsed += dt;
+        self.position_at(self.elapsed)
+    }
+
+    pub fn position_at(&self, time: Real) -> Vector {
+        match &self.path {
+            Path::Linear { from, to, period } => {
+                let phase = (time / *period).rem_euclid(Real::ONE);
+                let along = 1.0 - (1.0 - 2.0 * phase).abs();
+
+                math::vector(*from).lerp(math::vector(*to), along)
+            }
+            Path::Circular {
+                center,
+                radius,
+                period,
+            } => math::vector(*center) + Vector::from_angle(TAU * time / *period) * *radius,
+            Path::Spline {
+                points,
+                segment_duration,
+            } => {
+                let along = (time / *segment_duration).rem_euclid(math::real(points.len() as f32));
+                let segment = math::to_f32(along.floor()) as usize;
+
+                let point = |offset: usize| math::vector(points[(segment + offset) % points.len()]);
+
+                catmull_rom(
+                    point(points.len() - 1),
+                    point(0),
+                    point(1),
+                    point(2),
+                    along.fract(),
+                )
+            }
+        }
+    }
+}
+
+/// Point `t` of the way from `b` to `c` on a curve also going through `a` and `d`.
+fn catmull_rom(a: Vector, b: Vector, c: Vector, d: Vector, t: Real) -> Vector {
+    let (t2, t3) = (t * t, t * t * t);
+
+    0.5 * (2.0 * b
+        + (c - a) * t
+        + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2
+        + (3.0 * b - a - 3.0 * c + d) * t3)
+}
@@ -0,0 +1,346 @@
+use crate::ecs::entity::Entity;
+use crate::ecs::world::query::QueryState;
+use crate::ecs::world::World;
+use crate::geometry::Aabb;
+use crate::geometry::Circle;
+use crate::geometry::Shape;
+use crate::math;
+use crate::math::Real;
+use crate::math::Scalar;
+use crate::math::Vector;
+
+use super::body::Collider;
+use super::body::Position;
+use super::body::Rotation;
+use super::broadphase::SpatialGrid;
+use super::collision;
+use super::collision::Contact;
+use super::collision::Impact;
+
+#[derive(Debug, Clone)]
+pub struct RayHit {
+    pub entity: Entity,
+    pub point: Vector,
+    /// Normal of the surface that was hit, facing the ray.
+    pub normal: Vector,
+    /// Distance from the ray origin to `point`.
+    pub distance: Real,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShapeHit {
+    pub entity: Entity,
+    /// Where the shape stops, touching the hit collider.
+    pub position: Vector,
+    /// Fraction of the way from the start to the end, from 0 to 1.
+    pub time: Real,
+    /// Contact seen from the cast shape.
+    pub contact: Contact,
+}
+
+/// Casts rays and shapes against the colliders in the world, as of the last
+/// physics step. Sensors are never hit.
+pub struct PhysicsQuery {
+    colliders: QueryState<(Entity, &'static Collider)>,
+    collider: QueryState<(&'static Position, &'static Collider)>,
+    rotations: QueryState<&'static Rotation>,
+}
+
+impl PhysicsQuery {
+    pub fn new(world: &mut World) -> PhysicsQuery {
+        PhysicsQuery {
+            colliders: world.query(),
+            collider: world.query(),
+            rotations: world.query(),
+        }
+    }
+
+    /// First collider on one of the `mask` layers hit by a ray going from
+    /// `origin` towards `direction`, up to `max_distance` away. A ray starting
+    /// inside a collider hits it at a distance of 0.
+    pub fn raycast(
+        &mut self,
+        world: &World,
+        origin: Vector,
+        direction: Vector,
+        max_distance: Real,
+        mask: u32,
+    ) -> Option<RayHit> {
+        let displacement = direction.normalize_or_zero() * max_distance;
+
+        // A ray is a point being swept.
+        let point = Shape::Circle(Circle::new(origin, 0.0));
+
+        self.cast(world, &point, displacement, mask)
+            .map(|(entity, time, contact)| RayHit {
+                entity,
+                point: contact.point,
+                normal: contact.normal,
+                // Measured to the point rather than scaled from the time, which
+                // fixed point numbers only keep to 1/65536 of the ray.
+                distance: if time > 0.0 {
+                    origin.distance(contact.point)
+                } else {
+                    Real::ZERO
+                },
+            })
+    }
+
+    /// First collider on one of the `mask` layers hit by `shape`, relative to
+    /// `from`, when moving it to `to`.
+    pub fn shape_cast(
+        &mut self,
+        world: &World,
+        shape: &Shape,
+        from: Vector,
+        to: Vector,
+        mask: u32,
+    ) -> Option<ShapeHit> {
+        let displacement = to - from;
+
+        self.cast(world, &shape.translate(from), displacement, mask)
+            .map(|(entity, time, contact)| ShapeHit {
+                entity,
+                position: from + displacement * time,
+                time,
+                contact,
+            })
+    }
+
+    fn cast(
+        &mut self,
+        world: &World,
+        shape: &Shape,
+        displacement: Vector,
+        mask: u32,
+    ) -> Option<(Entity, Real, Contact)> {
+        let region = shape
+            .bounds()
+            .union(&shape.translate(displacement).bounds());
+
+        let mut earliest: Option<(Entity, Real, Contact)> = None;
+
+        for entity in self.candidates(world, &region) {
+            let (Some(position), Some(collider)) = self.collider.get(world, entity.clone()) else {
+                continue;
+            };
+
+            if collider.sensor || collider.layer & mask == 0 {
+                continue;
+            }
+
+            let rotation = self
+                .rotations
+                .get(world, entity.clone())
+                .map(|rotation| math::real(rotation.0))
+                .unwrap_or_default();
+
+            let other = collider.place(math::vector(position.0), rotation);
+
+            // Shapes starting inside a collider hit it straight away, whichever
+            // way they are going.
+            let Some(impact) = collision::collide(shape, &other)
+                .map(|contact| Impact {
+                    time: Real::ZERO,
+                    contact,
+                })
+                .or_else(|| collision::sweep(shape, displacement, &other))
+            else {
+                continue;
+            };
+
+            if earliest
+                .as_ref()
+                .is_none_or(|(_, time, _)| impact.time < *time)
+            {
+                earliest = Some((entity, impact.time, impact.contact));
+            }
+        }
+
+        earliest
+    }
+
+    /// Entities which may be in `region`, every collider when there is no
+    /// broadphase.
+    fn candidates(&mut self, world: &World, region: &Aabb) -> Vec<Entity> {
+        match world.resource::<SpatialGrid>() {
+            Some(grid) => grid.query_region(region),
+            None => self
+                .colliders
+                .iter(world)
+                .map(|(entity, _)| entity)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ggez::glam::Vec2;
+
+    use crate::math::vec2;
+
+    use super::*;
+
+    /// Boxes 20 wide every 100 along the x axis, the second one only on layer 2.
+    fn world() -> (World, Vec<Entity>) {
+        let mut world = World::new();
+
+        let boxes = (1..=3)
+            .map(|i| {
+                let collider = Collider::new(Aabb::new(vec2(-10.0, -10.0), 20.0, 20.0));
+                let collider = match i {
+                    2 => collider.with_layer(2),
+                    _ => collider,
+                };
+
+                world.spawn((Position(Vec2::new(i as f32 * 100.0, 0.0)), collider))
+            })
+            .collect();
+
+        (world, boxes)
+    }
+
+    #[test]
+    fn raycast_hits_the_earliest_collider() {
+        let (mut world, boxes) = world();
+        let mut query = PhysicsQuery::new(&mut world);
+
+        let hit = query
+            .raycast(
+                &world,
+                Vector::ZERO,
+                Vector::X,
+                math::real(1000.0),
+                u32::MAX,
+            )
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[0]);
+        assert_eq!(hit.normal, -Vector::X);
+        assert!((hit.distance - 90.0).abs() < 1e-3, "{}", hit.distance);
+        assert!(
+            (hit.point - vec2(90.0, 0.0)).length() < 1e-3,
+            "{}",
+            hit.point
+        );
+
+        let hit = query
+            .raycast(
+                &world,
+                vec2(400.0, 0.0),
+                -Vector::X,
+                math::real(1000.0),
+                u32::MAX,
+            )
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[2]);
+    }
+
+    #[test]
+    fn raycast_skips_masked_layers_and_sensors() {
+        let (mut world, boxes) = world();
+        let mut query = PhysicsQuery::new(&mut world);
+
+        let hit = query
+            .raycast(&world, vec2(150.0, 0.0), Vector::X, math::real(1000.0), 1)
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[2]);
+
+        let hit = query
+            .raycast(&world, vec2(150.0, 0.0), Vector::X, math::real(1000.0), 2)
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[1]);
+
+        world.spawn((
+            Position(Vec2::new(50.0, 0.0)),
+            Collider::new(Circle::new(Vector::ZERO, 5.0)).as_sensor(),
+        ));
+
+        let hit = query
+            .raycast(
+                &world,
+                Vector::ZERO,
+                Vector::X,
+                math::real(1000.0),
+                u32::MAX,
+            )
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[0]);
+    }
+
+    #[test]
+    fn raycast_stops_at_max_distance() {
+        let (mut world, boxes) = world();
+        let mut query = PhysicsQuery::new(&mut world);
+
+        assert!(query
+            .raycast(&world, Vector::ZERO, Vector::X, math::real(89.0), u32::MAX)
+            .is_none());
+
+        let hit = query
+            .raycast(&world, Vector::ZERO, Vector::X, math::real(91.0), u32::MAX)
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[0]);
+    }
+
+    #[test]
+    fn casts_starting_inside_a_collider_hit_it_at_once() {
+        let (mut world, boxes) = world();
+        let mut query = PhysicsQuery::new(&mut world);
+
+        // Leaving the box the ray starts in.
+        let hit = query
+            .raycast(
+                &world,
+                vec2(105.0, 0.0),
+                Vector::X,
+                math::real(1000.0),
+                u32::MAX,
+            )
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[0]);
+        assert_eq!(hit.distance, 0.0);
+
+        let ball = Shape::Circle(Circle::new(Vector::ZERO, 5.0));
+
+        let hit = query
+            .shape_cast(&world, &ball, vec2(88.0, 0.0), vec2(0.0, 0.0), u32::MAX)
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[0]);
+        assert_eq!(hit.time, 0.0);
+        assert_eq!(hit.position, vec2(88.0, 0.0));
+    }
+
+    #[test]
+    fn shape_cast_stops_touching_the_collider() {
+        let (mut world, boxes) = world();
+        let mut query = PhysicsQuery::new(&mut world);
+
+        let ball = Shape::Circle(Circle::new(Vector::ZERO, 5.0));
+
+        let hit = query
+            .shape_cast(&world, &ball, Vector::ZERO, vec2(170.0, 0.0), u32::MAX)
+            .unwrap();
+
+        assert_eq!(hit.entity, boxes[0]);
+        assert!(
+            (hit.position - vec2(85.0, 0.0)).length() < 1e-3,
+            "{}",
+            hit.position
+        );
+        assert!((hit.time - 0.5).abs() < 1e-3, "{}", hit.time);
+        assert_eq!(hit.contact.normal, -Vector::X);
+
+        assert!(query
+            .shape_cast(&world, &ball, Vector::ZERO, vec2(80.0, 0.0), u32::MAX)
+            .is_none());
+    }
+}
@@ -0,0 +1,933 @@
+//! Capsules dropped by bricks and the timed effects they give when caught.
+//!
+//! Effects only live in [`Effects`], every system derives the paddle width,
+//! ball mask and ball look from it each step. Ending an effect, or clearing
+//! all of them when a ball is lost, reverts everything on the next step.
+//! Ball speeds are only rescaled when the slow ball effect starts or ends,
+//! so bounces keep changing them in between.
+
+use breakout::app::FixedTime;
+use breakout::ecs::entity::Entity;
+use breakout::ecs::event::Events;
+use breakout::ecs::world::query::QueryState;
+use breakout::ecs::world::World;
+use breakout::geometry::Aabb;
+use breakout::level::Level;
+use breakout::level::Progress;
+use breakout::math;
+use breakout::physics::body::Collider;
+use breakout::physics::body::Position;
+use breakout::physics::body::RigidBody;
+use breakout::physics::body::Velocity;
+use breakout::physics::broadphase::SpatialGrid;
+use breakout::physics::collision;
+use breakout::physics::step::CollisionStarted;
+use breakout_macros::Component;
+use ggez::glam::vec2;
+use ggez::glam::Vec2;
+use ggez::graphics::Canvas;
+use ggez::graphics::Color;
+use ggez::graphics::DrawMode;
+use ggez::graphics::DrawParam;
+use ggez::graphics::Mesh;
+use ggez::graphics::Rect;
+use ggez::graphics::Text;
+use ggez::graphics::TextLayout;
+use ggez::input::keyboard::KeyCode;
+use ggez::Context;
+use ggez::GameError;
+use rand::Rng;
+
+use super::interpolate;
+use super::spawn_ball_at;
+use super::upwards;
+use super::Damage;
+use super::GameState;
+use super::Life;
+use super::Paddle;
+use super::PaddleBounce;
+use super::PreviousPosition;
+use super::Shape;
+use super::BALL_RADIUS;
+use super::LAYER_BRICK;
+use super::LAYER_LASER;
+use super::LAYER_PADDLE;
+use super::LAYER_POWER_UP;
+use super::LAYER_WALL;
+use super::MAX_LIVES;
+use super::PLAYER_HEIGHT;
+use super::PLAYER_WIDTH;
+
+const CAPSULE_WIDTH: f32 = 40.0;
+const CAPSULE_HEIGHT: f32 = 16.0;
+const CAPSULE_SPEED: f32 = 150.0;
+
+const WIDE_PADDLE_SCALE: f32 = 1.5;
+const SLOW_BALL_SCALE: f32 = 0.6;
+
+const LASER_WIDTH: f32 = 4.0;
+const LASER_HEIGHT: f32 = 16.0;
+const LASER_SPEED: f32 = 600.0;
+/// Seconds between two shots.
+const LASER_COOLDOWN: f32 = 0.25;
+
+/// Angle between the ball and each of the two balls split from it.
+const MULTI_BALL_SPREAD: f32 = 0.35;
+const MAX_BALLS: usize = 12;
+/// Longest an effect can be extended to, in seconds.
+const MAX_DURATION: f32 = 30.0;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Component)]
+pub enum PowerUp {
+    WidePaddle,
+    /// Splits every ball in three.
+    MultiBall,
+    SlowBall,
+    /// Balls stick to the paddle until launched with space.
+    Sticky,
+    /// Space shoots lasers from both sides of the paddle.
+    Laser,
+    ExtraLife,
+    /// Balls go through bricks, breaking them in one hit.
+    Fireball,
+}
+
+/// What catching a power-up does while it is already in effect.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Stacking {
+    /// Starts the duration over.
+    Refresh,
+    /// Adds the duration to the time left.
+    Extend,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 7] = [
+        PowerUp::WidePaddle,
+        PowerUp::MultiBall,
+        PowerUp::SlowBall,
+        PowerUp::Sticky,
+        PowerUp::Laser,
+        PowerUp::ExtraLife,
+        PowerUp::Fireball,
+    ];
+
+    /// How often it drops compared to the others.
+    pub fn weight(self) -> u32 {
+        match self {
+            PowerUp::ExtraLife => 1,
+            PowerUp::MultiBall | PowerUp::Fireball => 2,
+            _ => 3,
+        }
+    }
+
+    pub fn random(rng: &mut impl Rng) -> PowerUp {
+        let total = PowerUp::ALL.iter().map(|power_up| power_up.weight()).sum();
+        let mut roll = rng.gen_range(0..total);
+
+        for power_up in PowerUp::ALL {
+            if roll < power_up.weight() {
+                return power_up;
+            }
+
+            roll -= power_up.weight();
+        }
+
+        unreachable!("Roll is always below the total weight")
+    }
+
+    /// Seconds it lasts, `None` for the ones applied once when caught.
+    pub fn duration(self) -> Option<f32> {
+        match self {
+            PowerUp::WidePaddle => Some(15.0),
+            PowerUp::SlowBall => Some(12.0),
+            PowerUp::Sticky => Some(15.0),
+            PowerUp::Laser => Some(10.0),
+            PowerUp::Fireball => Some(8.0),
+            PowerUp::MultiBall | PowerUp::ExtraLife => None,
+        }
+    }
+
+    pub fn stacking(self) -> Stacking {
+        match self {
+            PowerUp::Laser => Stacking::Extend,
+            _ => Stacking::Refresh,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUp::WidePaddle => "Wide paddle",
+            PowerUp::MultiBall => "Multi-ball",
+            PowerUp::SlowBall => "Slow ball",
+            PowerUp::Sticky => "Sticky paddle",
+            PowerUp::Laser => "Laser",
+            PowerUp::ExtraLife => "Extra life",
+            PowerUp::Fireball => "Fireball",
+        }
+    }
+
+    /// Drawn on the capsule.
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerUp::WidePaddle => "W",
+            PowerUp::MultiBall => "M",
+            PowerUp::SlowBall => "S",
+            PowerUp::Sticky => "C",
+            PowerUp::Laser => "L",
+            PowerUp::ExtraLife => "+",
+            PowerUp::Fireball => "F",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            PowerUp::WidePaddle => Color::from_rgb(65, 105, 225),
+            PowerUp::MultiBall => Color::from_rgb(220, 220, 220),
+            PowerUp::SlowBall => Color::from_rgb(255, 165, 0),
+            PowerUp::Sticky => Color::from_rgb(50, 205, 50),
+            PowerUp::Laser => Color::from_rgb(220, 20, 60),
+            PowerUp::ExtraLife => Color::from_rgb(255, 105, 180),
+            PowerUp::Fireball => Color::from_rgb(255, 69, 0),
+        }
+    }
+}
+
+/// Power-ups in effect, with the seconds they have left.
+#[derive(Debug, Clone, Default)]
+pub struct Effects(Vec<(PowerUp, f32)>);
+
+impl Effects {
+    /// Starts `power_up`, or stacks it with the one in effect.
+    pub fn add(&mut self, power_up: PowerUp) {
+        let Some(duration) = power_up.duration() else {
+            return;
+        };
+
+        match self.0.iter_mut().find(|(active, _)| *active == power_up) {
+            Some((_, remaining)) => match power_up.stacking() {
+                Stacking::Refresh => *remaining = duration,
+                Stacking::Extend => *remaining = (*remaining + duration).min(MAX_DURATION),
+            },
+            None => self.0.push((power_up, duration)),
+        }
+    }
+
+    #[inline]
+    pub fn is_active(&self, power_up: PowerUp) -> bool {
+        self.0.iter().any(|(active, _)| *active == power_up)
+    }
+
+    /// Counts `dt` seconds down, ending the effects out of time.
+    pub fn tick(&mut self, dt: f32) {
+        for (_, remaining) in self.0.iter_mut() {
+            *remaining -= dt;
+        }
+
+        self.0.retain(|(_, remaining)| *remaining > 0.0);
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(PowerUp, f32)> {
+        self.0.iter()
+    }
+}
+
+pub struct PowerUpState {
+    effects: Effects,
+    /// Balls held by the sticky paddle, with their offset from its left edge.
+    stuck: Vec<(Entity, f32)>,
+    laser_cooldown: f32,
+    /// Speed scale the balls were last rescaled to.
+    ball_scale: f32,
+    ball: Mesh,
+    fireball: Mesh,
+    laser: Mesh,
+    queries: Queries,
+}
+
+struct Queries {
+    paddle: QueryState<(
+        &'static mut Position,
+        &'static mut Paddle,
+        &'static mut Collider,
+        &'static mut Shape,
+    )>,
+    paddles: QueryState<(&'static Position, &'static Paddle)>,
+    positions: QueryState<&'static Position>,
+    moving: QueryState<(&'static mut Position, &'static mut Velocity)>,
+    balls: QueryState<(
+        &'static mut Velocity,
+        &'static mut Collider,
+        &'static mut Shape,
+    )>,
+    bodies: QueryState<(&'static Position, &'static Collider)>,
+    lives: QueryState<&'static Life>,
+    kinds: QueryState<&'static PowerUp>,
+    labels: QueryState<(
+        &'static Position,
+        &'static PowerUp,
+        &'static PreviousPosition,
+    )>,
+}
+
+impl Queries {
+    fn new(world: &mut World) -> Self {
+        Self {
+            paddle: world.query(),
+            paddles: world.query(),
+            positions: world.query(),
+            moving: world.query(),
+            balls: world.query(),
+            bodies: world.query(),
+            lives: world.query(),
+            kinds: world.query(),
+            labels: world.query(),
+        }
+    }
+}
+
+pub fn setup(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let ball = |color| Mesh::new_circle(ctx, DrawMode::fill(), Vec2::ZERO, BALL_RADIUS, 0.1, color);
+
+    let laser = Mesh::new_rectangle(
+        ctx,
+        DrawMode::fill(),
+        Rect::new(0.0, 0.0, LASER_WIDTH, LASER_HEIGHT),
+        PowerUp::Laser.color(),
+    )?;
+
+    let queries = Queries::new(world);
+
+    world.insert_resource(PowerUpState {
+        effects: Effects::default(),
+        stuck: vec![],
+        laser_cooldown: 0.0,
+        ball_scale: 1.0,
+        ball: ball(Color::WHITE)?,
+        fireball: ball(PowerUp::Fireball.color())?,
+        laser,
+        queries,
+    });
+
+    Ok(())
+}
+
+/// Ends every effect, the systems put everything back on the next step.
+pub fn clear_effects(world: &mut World) {
+    if let Some(mut state) = world.resource_mut::<PowerUpState>() {
+        state.effects.clear();
+        state.stuck.clear();
+        state.ball_scale = 1.0;
+    }
+}
+
+/// Capsule falling from `center` for the paddle to catch.
+pub fn spawn_capsule(
+    world: &mut World,
+    ctx: &mut Context,
+    center: Vec2,
+    power_up: PowerUp,
+) -> Result<Entity, GameError> {
+    let rect = Rect::new(0.0, 0.0, CAPSULE_WIDTH, CAPSULE_HEIGHT);
+    let mesh = Mesh::new_rounded_rectangle(
+        ctx,
+        DrawMode::fill(),
+        rect,
+        CAPSULE_HEIGHT * 0.5,
+        power_up.color(),
+    )?;
+
+    let position = center - vec2(CAPSULE_WIDTH, CAPSULE_HEIGHT) * 0.5;
+
+    Ok(world.spawn((
+        Position(position),
+        PreviousPosition(position),
+        Velocity(vec2(0.0, CAPSULE_SPEED)),
+        Shape(mesh),
+        power_up,
+        RigidBody::Kinematic,
+        Collider::new(Aabb::new(Vec2::ZERO, CAPSULE_WIDTH, CAPSULE_HEIGHT))
+            .with_layer(LAYER_POWER_UP)
+            .with_mask(LAYER_PADDLE)
+            .as_sensor(),
+    )))
+}
+
+/// Speed scale the effects in place call for.
+fn ball_scale(effects: &Effects) -> f32 {
+    if effects.is_active(PowerUp::SlowBall) {
+        SLOW_BALL_SCALE
+    } else {
+        1.0
+    }
+}
+
+/// Level ball speed at `scale`, for launching balls.
+fn ball_speed(world: &World, scale: f32) -> f32 {
+    world
+        .resource::<Level>()
+        .expect("Could not find Level resource")
+        .ball_speed
+        * scale
+}
+
+pub fn update_effects(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let dt = world
+        .resource::<FixedTime>()
+        .expect("Could not find FixedTime resource")
+        .delta_secs();
+
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+
+    state.effects.tick(dt);
+    state.laser_cooldown = (state.laser_cooldown - dt).max(0.0);
+
+    Ok(())
+}
+
+/// Grows the paddle around its center while wide, and shrinks it back after.
+pub fn resize_paddle(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let gs = world
+        .resource::<GameState>()
+        .expect("Could not find GameState resource");
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+    let ps = &mut *state;
+
+    let width = if ps.effects.is_active(PowerUp::WidePaddle) {
+        PLAYER_WIDTH * WIDE_PADDLE_SCALE
+    } else {
+        PLAYER_WIDTH
+    };
+
+    let grown = match ps.queries.paddle.get(world, gs.player.clone()) {
+        (Some(mut position), Some(mut paddle), Some(mut collider), Some(mut shape)) => {
+            if paddle.width == width {
+                return Ok(());
+            }
+
+            let grown = width - paddle.width;
+            let x = position.0.x - grown * 0.5;
+
+            position.0.x = x.clamp(0.0, ctx.gfx.size().0 - width);
+            paddle.width = width;
+            collider.shape = Aabb::new(Vec2::ZERO, width, PLAYER_HEIGHT).into();
+            shape.0 = Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                Rect::new(0.0, 0.0, width, PLAYER_HEIGHT),
+                Color::WHITE,
+            )?;
+
+            grown
+        }
+        _ => panic!("Could not find components to resize Player"),
+    };
+
+    // Held balls keep their place as the paddle grows around them.
+    for (_, offset) in ps.stuck.iter_mut() {
+        *offset += grown * 0.5;
+    }
+
+    Ok(())
+}
+
+/// Launches the balls held by the paddle on space, or when it stops being sticky.
+pub fn release_balls(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let bounce = *world
+        .resource::<PaddleBounce>()
+        .expect("Could not find PaddleBounce resource");
+
+    let gs = world
+        .resource::<GameState>()
+        .expect("Could not find GameState resource");
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+    let ps = &mut *state;
+
+    let should_release = !ps.stuck.is_empty()
+        && (!ps.effects.is_active(PowerUp::Sticky) || ctx.keyboard.is_key_pressed(KeyCode::Space));
+
+    if !should_release {
+        return Ok(());
+    }
+
+    let width = match ps.queries.paddles.get(world, gs.player.clone()) {
+        (_, Some(paddle)) => paddle.width,
+        _ => panic!("Could not find components to release balls from Player"),
+    };
+
+    let speed = ball_speed(world, ps.ball_scale);
+
+    for (ball, offset) in ps.stuck.drain(..) {
+        // Aimed by where the ball sits, like a bounce on the paddle.
+        let along = ((offset - width * 0.5) / (width * 0.5)).clamp(-1.0, 1.0);
+
+        if let (_, Some(mut velocity)) = ps.queries.moving.get(world, ball) {
+            velocity.0 = upwards(along * bounce.max_angle) * speed;
+        }
+    }
+
+    Ok(())
+}
+
+/// Keeps the balls at the mask and look the effects call for, and rescales
+/// their speed when the slow ball effect starts or ends.
+pub fn apply_ball_effects(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let gs = world
+        .resource::<GameState>()
+        .expect("Could not find GameState resource");
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+    let ps = &mut *state;
+
+    let scale = ball_scale(&ps.effects);
+    let rescale = scale / ps.ball_scale;
+    let fireball = ps.effects.is_active(PowerUp::Fireball);
+
+    ps.ball_scale = scale;
+
+    for ball in gs.balls.iter() {
+        let (Some(mut velocity), Some(mut collider), Some(mut shape)) =
+            ps.queries.balls.get(world, ball.clone())
+        else {
+            panic!("Could not find components to update Ball {:?}", ball);
+        };
+
+        if rescale != 1.0 {
+            velocity.0 *= rescale;
+        }
+
+        // Fireballs go through bricks, breaking them in `burn_bricks` instead.
+        if fireball {
+            collider.mask &= !LAYER_BRICK;
+            shape.0 = ps.fireball.clone();
+        } else {
+            collider.mask |= LAYER_BRICK;
+            shape.0 = ps.ball.clone();
+        }
+    }
+
+    Ok(())
+}
+
+pub fn fire_lasers(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+    let ps = &mut *state;
+
+    let should_fire = ps.effects.is_active(PowerUp::Laser)
+        && ps.laser_cooldown <= 0.0
+        && ctx.keyboard.is_key_pressed(KeyCode::Space);
+
+    if !should_fire {
+        return Ok(());
+    }
+
+    ps.laser_cooldown = LASER_COOLDOWN;
+
+    let (paddle, width) = {
+        let gs = world
+            .resource::<GameState>()
+            .expect("Could not find GameState resource");
+
+        match ps.queries.paddles.get(world, gs.player.clone()) {
+            (Some(position), Some(paddle)) => (position.0, paddle.width),
+            _ => panic!("Could not find components to fire from Player"),
+        }
+    };
+
+    let mesh = ps.laser.clone();
+
+    drop(state);
+
+    for x in [0.0, width - LASER_WIDTH] {
+        let position = paddle + vec2(x, -LASER_HEIGHT);
+
+        let laser = world.spawn((
+            Position(position),
+            PreviousPosition(position),
+            Velocity(vec2(0.0, -LASER_SPEED)),
+            Shape(mesh.clone()),
+            RigidBody::Kinematic,
+            Collider::new(Aabb::new(Vec2::ZERO, LASER_WIDTH, LASER_HEIGHT))
+                .with_layer(LAYER_LASER)
+                .with_mask(LAYER_BRICK | LAYER_WALL)
+                .as_sensor(),
+        ));
+
+        if let Some(mut gs) = world.resource_mut::<GameState>() {
+            gs.lasers.push(laser);
+        }
+    }
+
+    Ok(())
+}
+
+/// Holds the balls landing on top of the sticky paddle.
+pub fn catch_balls(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let gs = world
+        .resource::<GameState>()
+        .expect("Could not find GameState resource");
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+    let ps = &mut *state;
+
+    if !ps.effects.is_active(PowerUp::Sticky) {
+        return Ok(());
+    }
+
+    let started = world
+        .resource::<Events<CollisionStarted>>()
+        .expect("Could not find CollisionStarted events");
+
+    for CollisionStarted(collision) in started.iter() {
+        if collision.b != gs.player || !gs.balls.contains(&collision.a) {
+            continue;
+        }
+
+        if collision.contact.normal.y >= 0.0
+            || ps.stuck.iter().any(|(ball, _)| *ball == collision.a)
+        {
+            continue;
+        }
+
+        let (Some(ball), Some(paddle)) = (
+            ps.queries.positions.get(world, collision.a.clone()),
+            ps.queries.positions.get(world, collision.b.clone()),
+        ) else {
+            continue;
+        };
+
+        ps.stuck.push((collision.a.clone(), ball.0.x - paddle.0.x));
+    }
+
+    Ok(())
+}
+
+/// Keeps the held balls resting on the paddle as it moves.
+pub fn pin_balls(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let gs = world
+        .resource::<GameState>()
+        .expect("Could not find GameState resource");
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+    let ps = &mut *state;
+
+    let (paddle, width) = match ps.queries.paddles.get(world, gs.player.clone()) {
+        (Some(position), Some(paddle)) => (position.0, paddle.width),
+        _ => panic!("Could not find components to hold balls on Player"),
+    };
+
+    for (ball, offset) in ps.stuck.iter() {
+        if let (Some(mut position), Some(mut velocity)) = ps.queries.moving.get(world, ball.clone())
+        {
+            // Just above the paddle, so it is not pushed out of it.
+            position.0 = paddle + vec2(offset.clamp(0.0, width), -BALL_RADIUS - 0.5);
+            velocity.0 = Vec2::ZERO;
+        }
+    }
+
+    Ok(())
+}
+
+/// Breaks every brick a fireball touches.
+pub fn burn_bricks(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let gs = world
+        .resource::<GameState>()
+        .expect("Could not find GameState resource");
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+    let ps = &mut *state;
+
+    if !ps.effects.is_active(PowerUp::Fireball) {
+        return Ok(());
+    }
+
+    let grid = world
+        .resource::<SpatialGrid>()
+        .expect("Could not find SpatialGrid resource");
+    let mut damage = world
+        .resource_mut::<Events<Damage>>()
+        .expect("Could not find Damage events");
+
+    for ball in gs.balls.iter() {
+        let ball = match ps.queries.bodies.get(world, ball.clone()) {
+            (Some(position), Some(collider)) => collider.place(position.0, 0.0),
+            _ => panic!("Could not find components to burn with Ball {:?}", ball),
+        };
+
+        for brick in grid.query_region(&ball.bounds()) {
+            if !gs.blocks.contains(&brick) || ps.queries.lives.get(world, brick.clone()).is_none() {
+                continue;
+            }
+
+            let (Some(position), Some(collider)) = ps.queries.bodies.get(world, brick.clone())
+            else {
+                continue;
+            };
+
+            if collision::collide(&ball, &collider.place(position.0, 0.0)).is_some() {
+                damage.send(Damage {
+                    brick,
+                    amount: u8::MAX,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Damages the bricks hit by lasers, which stop at the first thing they hit.
+pub fn check_laser_hits(world: &mut World) -> Result<(), GameError> {
+    let mut hit = Vec::new();
+
+    {
+        let mut state = world
+            .resource_mut::<GameState>()
+            .expect("Could not find GameState resource");
+        let gs = &mut *state;
+
+        let started = world
+            .resource::<Events<CollisionStarted>>()
+            .expect("Could not find CollisionStarted events");
+        let mut damage = world
+            .resource_mut::<Events<Damage>>()
+            .expect("Could not find Damage events");
+
+        for CollisionStarted(collision) in started.iter() {
+            let (laser, other) = if gs.lasers.contains(&collision.a) {
+                (&collision.a, &collision.b)
+            } else if gs.lasers.contains(&collision.b) {
+                (&collision.b, &collision.a)
+            } else {
+                continue;
+            };
+
+            if gs.blocks.contains(other) {
+                damage.send(Damage {
+                    brick: other.clone(),
+                    amount: 1,
+                });
+            }
+
+            if !hit.contains(laser) {
+                hit.push(laser.clone());
+            }
+        }
+
+        gs.lasers.retain(|laser| !hit.contains(laser));
+    }
+
+    for laser in hit {
+        world.despawn(laser);
+    }
+
+    Ok(())
+}
+
+/// Applies the capsules caught by the paddle, and removes the ones that are
+/// caught or fell off the screen.
+pub fn catch_power_ups(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let mut caught = Vec::new();
+    let mut should_destroy = Vec::new();
+
+    {
+        let mut state = world
+            .resource_mut::<GameState>()
+            .expect("Could not find GameState resource");
+        let gs = &mut *state;
+        let mut power_ups = world
+            .resource_mut::<PowerUpState>()
+            .expect("Could not find PowerUpState resource");
+        let ps = &mut *power_ups;
+
+        let started = world
+            .resource::<Events<CollisionStarted>>()
+            .expect("Could not find CollisionStarted events");
+
+        for CollisionStarted(collision) in started.iter() {
+            let capsule = if collision.b == gs.player {
+                &collision.a
+            } else if collision.a == gs.player {
+                &collision.b
+            } else {
+                continue;
+            };
+
+            if !gs.power_ups.contains(capsule) || should_destroy.contains(capsule) {
+                continue;
+            }
+
+            if let Some(power_up) = ps.queries.kinds.get(world, capsule.clone()) {
+                caught.push(*power_up);
+            }
+
+            should_destroy.push(capsule.clone());
+        }
+
+        for capsule in gs.power_ups.iter() {
+            match ps.queries.positions.get(world, capsule.clone()) {
+                Some(position) => {
+                    if position.0.y > ctx.gfx.size().1 {
+                        should_destroy.push(capsule.clone());
+                    }
+                }
+                None => panic!("Could not find components to update PowerUp {:?}", capsule),
+            }
+        }
+
+        gs.power_ups
+            .retain(|capsule| !should_destroy.contains(capsule));
+    }
+
+    for capsule in should_destroy {
+        world.despawn(capsule);
+    }
+
+    for power_up in caught {
+        apply(world, ctx, power_up)?;
+    }
+
+    Ok(())
+}
+
+fn apply(world: &mut World, ctx: &mut Context, power_up: PowerUp) -> Result<(), GameError> {
+    match power_up {
+        PowerUp::ExtraLife => {
+            if let Some(mut progress) = world.resource_mut::<Progress>() {
+                progress.lives = progress.lives.saturating_add(1).min(MAX_LIVES);
+            }
+        }
+        PowerUp::MultiBall => split_balls(world, ctx)?,
+        _ => {
+            if let Some(mut state) = world.resource_mut::<PowerUpState>() {
+                state.effects.add(power_up);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Spawns two more balls from each ball, spreading out from its direction.
+fn split_balls(world: &mut World, ctx: &mut Context) -> Result<(), GameError> {
+    let (mut count, sources) = {
+        let gs = world
+            .resource::<GameState>()
+            .expect("Could not find GameState resource");
+        let mut state = world
+            .resource_mut::<PowerUpState>()
+            .expect("Could not find PowerUpState resource");
+        let ps = &mut *state;
+
+        let speed = ball_speed(world, ps.ball_scale);
+
+        let sources: Vec<(Vec2, Vec2)> = gs
+            .balls
+            .iter()
+            .filter_map(|ball| match ps.queries.moving.get(world, ball.clone()) {
+                // Split balls keep the speed of theirs, held balls split upwards.
+                (Some(position), Some(velocity)) => Some((
+                    position.0,
+                    if velocity.0 == Vec2::ZERO {
+                        upwards(0.0) * speed
+                    } else {
+                        velocity.0
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+
+        (gs.balls.len(), sources)
+    };
+
+    for (position, velocity) in sources {
+        for angle in [-MULTI_BALL_SPREAD, MULTI_BALL_SPREAD] {
+            if count >= MAX_BALLS {
+                return Ok(());
+            }
+
+            let ball = spawn_ball_at(
+                world,
+                ctx,
+                position,
+                math::from_angle(angle).rotate(velocity),
+            )?;
+
+            if let Some(mut gs) = world.resource_mut::<GameState>() {
+                gs.balls.push(ball);
+            }
+
+            count += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Labels the capsules and lists the effects in place under the status line.
+pub fn draw_power_ups(world: &mut World, _ctx: &mut Context) -> Result<(), GameError> {
+    let gs = world
+        .resource::<GameState>()
+        .expect("Could not find GameState resource");
+    let mut state = world
+        .resource_mut::<PowerUpState>()
+        .expect("Could not find PowerUpState resource");
+    let ps = &mut *state;
+    let mut canvas = world
+        .resource_mut::<Canvas>()
+        .expect("Could not find Canvas resource");
+    let alpha = world
+        .resource::<FixedTime>()
+        .expect("Could not find FixedTime resource")
+        .alpha();
+
+    for capsule in gs.power_ups.iter() {
+        let (Some(position), Some(power_up), previous) =
+            ps.queries.labels.get(world, capsule.clone())
+        else {
+            panic!("Could not find components to draw PowerUp {:?}", capsule);
+        };
+
+        let mut label = Text::new(power_up.label());
+        label.set_layout(TextLayout::center());
+
+        let center = vec2(CAPSULE_WIDTH, CAPSULE_HEIGHT) * 0.5;
+
+        canvas.draw(
+            &label,
+            DrawParam::new()
+                .dest(interpolate(&position, previous, alpha) + center)
+                .color(Color::BLACK),
+        );
+    }
+
+    let effects: Vec<String> = ps
+        .effects
+        .iter()
+        .map(|(power_up, remaining)| format!("{} {:.0}s", power_up.name(), remaining.ceil()))
+        .collect();
+
+    if !effects.is_empty() {
+        canvas.draw(&Text::new(effects.join("    ")), vec2(10.0, 30.0));
+    }
+
+    Ok(())
+}